@@ -213,7 +213,7 @@ pub fn fill_center_building_groups(args: &FillCenterBuildingGroupsArgs) -> Resul
             }
         } else {
 
-            ft.set_geometry(output_geom)?;
+            ft.set_geometry(&output_geom)?;
             ft.create(&output_layer).unwrap();
 
         }
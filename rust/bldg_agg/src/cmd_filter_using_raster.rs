@@ -237,7 +237,7 @@ pub fn filter_using_raster(args: &FilterUsingRasterArgs) -> Result<()> {
 
             let mut ft = Feature::new(&output_layer_def)?;
 
-            ft.set_geometry(output_geom)?;
+            ft.set_geometry(&output_geom)?;
             ft.create(&output_lyr)?;
 
             // if n_processed % 10 == 0 {
@@ -112,7 +112,7 @@ pub fn fix_reproject_split(args: &FixReprojectSplitArgs) -> Result<()>
 
             let geom = input_feature.geometry().as_geom();
 
-            let mut fixed_geom = get_fixed_geom(geom);
+            let mut fixed_geom = get_fixed_geom(geom)?;
 
             if fixed_geom.geometry_type() != OGRwkbGeometryType::wkbMultiPolygon {
 
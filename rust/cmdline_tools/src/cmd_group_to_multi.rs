@@ -261,7 +261,7 @@ pub(crate) fn group_to_multi(args: &GroupToMultiArgs) -> Result<()> {
         //     ft.set_geometry(valid_grouped_geom)?;
         // } else {
             //debug!("Setting geometry");
-        ft.set_geometry(grouped_geom)?;
+        ft.set_geometry(&grouped_geom)?;
         //}
         ft.create(&output_lyr)?;
     }
@@ -99,7 +99,7 @@ pub fn to_single(args: &ToSingleArgs) -> Result<()> {
         match geometry_type {
             OGRwkbGeometryType::wkbPolygon => {
                 let mut ft = Feature::new(&output_layer_def)?;
-                ft.set_geometry(geom)?;
+                ft.set_geometry(&geom)?;
                 ft.create(&output_lyr)?;
             }
             OGRwkbGeometryType::wkbMultiPolygon => {
@@ -107,7 +107,7 @@ pub fn to_single(args: &ToSingleArgs) -> Result<()> {
                 for p in 0..poly_count {
                     let poly = geom.get_geometry(p);
                     let mut ft = Feature::new(&output_layer_def)?;
-                    ft.set_geometry(poly)?;
+                    ft.set_geometry(&poly)?;
                     ft.create(&output_lyr)?;
                 }
             }
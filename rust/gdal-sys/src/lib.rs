@@ -32,6 +32,13 @@ extern "C" {
     //call CPLFree / VSIFree afterwards
     
     
+    pub fn CSLDestroy(papszStrList: *mut *mut libc::c_char);
+}
+
+// Not in the generated 3.3 bindings
+#[cfg(feature = "min_gdal_version_3_4")]
+extern "C" {
+    pub fn OGR_G_MakeValidEx(hGeom: OGRGeometryH, papszOptions: CSLConstList) -> OGRGeometryH;
 }
 
 impl OGREnvelope {
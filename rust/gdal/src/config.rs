@@ -42,8 +42,10 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use anyhow::Result;
 use crate::utils::_string;
-use gdal_sys;
+use gdal_sys::{self, CPLErr, CPLErrorNum};
+use libc::{c_char, c_void};
 use std::ffi::CString;
+use std::panic::{self, AssertUnwindSafe};
 
 /// Set a GDAL library configuration option
 ///
@@ -84,9 +86,106 @@ pub fn clear_config_option(key: &str) -> Result<()> {
     Ok(())
 }
 
+type ErrorHandlerCallback = Box<dyn Fn(CPLErr::Type, i32, &str)>;
+
+unsafe extern "C" fn error_handler_trampoline(
+    err_class: CPLErr::Type,
+    err_no: CPLErrorNum,
+    msg: *const c_char,
+) {
+    let user_data = gdal_sys::CPLGetErrorHandlerUserData();
+    if user_data.is_null() {
+        return;
+    }
+    let callback = &*(user_data as *const ErrorHandlerCallback);
+    let msg = if msg.is_null() { String::new() } else { _string(msg) };
+    // a panic must not unwind across the C frame that called us
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| callback(err_class, err_no, &msg)));
+}
+
+/// Route GDAL errors and warnings of the current thread to `handler` instead of stderr
+///
+/// The handler receives the error class, the error number and the message.  Handlers
+/// are stacked, the handler stays installed until the returned guard is dropped.
+///
+/// ```
+/// use gdal::config::*;
+///
+/// {
+///     let _handler = push_error_handler(|class, number, msg| println!("{} {} {}", class, number, msg));
+///     // ... gdal calls ...
+/// }
+/// ```
+pub fn push_error_handler<F>(handler: F) -> ErrorHandlerGuard
+where
+    F: Fn(CPLErr::Type, i32, &str) + 'static,
+{
+    let callback: Box<ErrorHandlerCallback> = Box::new(Box::new(handler));
+    let callback = Box::into_raw(callback);
+    unsafe {
+        gdal_sys::CPLPushErrorHandlerEx(Some(error_handler_trampoline), callback as *mut c_void);
+    }
+    ErrorHandlerGuard { callback }
+}
+
+/// Keeps a handler installed by `push_error_handler`, pops and frees it when dropped
+///
+/// Guards must be dropped in reverse order of creation, on the thread that created them.
+#[must_use = "the error handler is removed as soon as the guard is dropped"]
+pub struct ErrorHandlerGuard {
+    callback: *mut ErrorHandlerCallback,
+}
+
+impl Drop for ErrorHandlerGuard {
+    fn drop(&mut self) {
+        unsafe {
+            gdal_sys::CPLPopErrorHandler();
+            drop(Box::from_raw(self.callback));
+        }
+    }
+}
+
+/// Suppresses GDAL error output on the current thread while alive
+///
+/// Use around calls that are expected to fail, the error is still returned by the
+/// wrapper functions, it is only not printed.
+///
+/// ```
+/// use gdal::config::ErrorSilencer;
+///
+/// {
+///     let _silencer = ErrorSilencer::new();
+///     // ... probing gdal calls ...
+/// }
+/// ```
+pub struct ErrorSilencer {
+    _private: (),
+}
+
+impl ErrorSilencer {
+    pub fn new() -> ErrorSilencer {
+        unsafe { gdal_sys::CPLPushErrorHandler(Some(gdal_sys::CPLQuietErrorHandler)) };
+        ErrorSilencer { _private: () }
+    }
+}
+
+impl Default for ErrorSilencer {
+    fn default() -> Self {
+        ErrorSilencer::new()
+    }
+}
+
+impl Drop for ErrorSilencer {
+    fn drop(&mut self) {
+        unsafe { gdal_sys::CPLPopErrorHandler() };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::spatial_ref::SpatialRef;
+    use std::sync::{Arc, Mutex};
     //use std::ffi::NulError;
 
     #[test]
@@ -122,4 +221,47 @@ mod tests {
             "DEFAULT"
         );
     }
+
+    #[test]
+    fn test_error_handler() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let captured = messages.clone();
+        let handler = push_error_handler(move |class, _number, msg| {
+            captured.lock().unwrap().push((class, msg.to_string()));
+        });
+
+        // 1 is not a valid EPSG code, GDAL reports it through the error handler
+        assert!(SpatialRef::from_epsg(1).is_err());
+
+        drop(handler);
+
+        let messages = messages.lock().unwrap();
+        assert!(!messages.is_empty());
+        assert!(messages.iter().any(|(class, _)| *class == CPLErr::CE_Failure));
+    }
+
+    #[test]
+    fn test_error_silencer() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let captured = messages.clone();
+        let handler = push_error_handler(move |_class, _number, msg| {
+            captured.lock().unwrap().push(msg.to_string());
+        });
+
+        {
+            let _silencer = ErrorSilencer::new();
+            assert!(SpatialRef::from_epsg(1).is_err());
+        }
+
+        drop(handler);
+
+        assert!(messages.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_error_handler_panic() {
+        let handler = push_error_handler(|_class, _number, _msg| panic!("handler panicked"));
+        assert!(SpatialRef::from_epsg(1).is_err());
+        drop(handler);
+    }
 }
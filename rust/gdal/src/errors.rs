@@ -51,7 +51,7 @@ pub enum ErrorKind {
     CastToF64Error,
     #[error("OGR method '{}' returned error: '{:?}'", method_name, err)]
     OgrError {
-        err: OGRErr::Type,
+        err: OgrError,
         method_name: &'static str,
     },
     #[error(
@@ -78,6 +78,25 @@ pub enum ErrorKind {
         index: usize,
         method_name: &'static str,
     },
+    #[error("Field '{}' is unset or null on method {}", field_name, method_name)]
+    NullField {
+        field_name: String,
+        method_name: &'static str,
+    },
+    #[error(
+        "Field '{}' has type {:?}, not a number, on method {}",
+        field_name, field_type, method_name
+    )]
+    NonNumericField {
+        field_name: String,
+        field_type: OGRFieldType::Type,
+        method_name: &'static str,
+    },
+    #[error("Layer '{}' does not support capability {}", layer_name, capability)]
+    UnsupportedCapability {
+        capability: &'static str,
+        layer_name: String,
+    },
     #[error("Unlinked Geometry on method {}", method_name)]
     UnlinkedGeometry { method_name: &'static str },
     #[error(
@@ -92,3 +111,37 @@ pub enum ErrorKind {
     #[error("Generic Error")]
     GenericError {}
 }
+
+/// OGRErr codes, so callers can match on OGR errors without gdal_sys
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OgrError {
+    NotEnoughData,
+    NotEnoughMemory,
+    UnsupportedGeometryType,
+    UnsupportedOperation,
+    CorruptData,
+    Failure,
+    UnsupportedSrs,
+    InvalidHandle,
+    NonExistingFeature,
+    /// A code this enum doesn't know about yet
+    Other(OGRErr::Type),
+}
+
+impl From<OGRErr::Type> for OgrError {
+    fn from(err: OGRErr::Type) -> Self {
+        match err {
+            OGRErr::OGRERR_NOT_ENOUGH_DATA => OgrError::NotEnoughData,
+            OGRErr::OGRERR_NOT_ENOUGH_MEMORY => OgrError::NotEnoughMemory,
+            OGRErr::OGRERR_UNSUPPORTED_GEOMETRY_TYPE => OgrError::UnsupportedGeometryType,
+            OGRErr::OGRERR_UNSUPPORTED_OPERATION => OgrError::UnsupportedOperation,
+            OGRErr::OGRERR_CORRUPT_DATA => OgrError::CorruptData,
+            OGRErr::OGRERR_FAILURE => OgrError::Failure,
+            OGRErr::OGRERR_UNSUPPORTED_SRS => OgrError::UnsupportedSrs,
+            // OGRERR_INVALID_HANDLE and OGRERR_NON_EXISTING_FEATURE are missing from the bindings
+            8 => OgrError::InvalidHandle,
+            9 => OgrError::NonExistingFeature,
+            other => OgrError::Other(other),
+        }
+    }
+}
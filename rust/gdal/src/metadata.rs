@@ -30,6 +30,29 @@ pub trait Metadata: MajorObject {
         Ok(_string(c_res))
     }
 
+    /// Names of the metadata domains of the object, the default domain is ""
+    fn metadata_domains(&self) -> Vec<String> {
+        let mut domains = Vec::new();
+        unsafe {
+            let c_domains = gdal_sys::GDALGetMetadataDomainList(self.gdal_object_ptr());
+            if c_domains.is_null() {
+                return domains;
+            }
+            let mut idx = 0;
+            loop {
+                let c_domain = *c_domains.offset(idx);
+                if c_domain.is_null() {
+                    break;
+                }
+                domains.push(_string(c_domain));
+                idx += 1;
+            }
+            //the list is owned by the caller
+            gdal_sys::CSLDestroy(c_domains);
+        }
+        domains
+    }
+
     fn metadata_item(&self, key: &str, domain: &str) -> Option<String> {
         if let Ok(c_key) = CString::new(key.to_owned()) {
             if let Ok(c_domain) = CString::new(domain.to_owned()) {
@@ -16,7 +16,7 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 pub use crate::spatial_ref::srs::CoordTransform;
-pub use crate::spatial_ref::srs::SpatialRef;
+pub use crate::spatial_ref::srs::{AxisMappingStrategy, DatumCriterion, SpatialRef};
 pub use gdal_sys::OSRAxisMappingStrategy;
 
 mod srs;
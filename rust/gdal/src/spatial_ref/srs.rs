@@ -19,16 +19,71 @@ use crate::utils::{_last_cpl_err, _last_null_pointer_err, _string};
 use gdal_sys::{self, CPLErr, OGRCoordinateTransformationH, OGRErr, OGRSpatialReferenceH, OSRAxisMappingStrategy};
 use libc::c_int;
 use std::ffi::{CStr, CString};
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
 use std::ptr;
 use std::str::FromStr;
 
 use crate::errors::*;
-use anyhow::Result;
+use anyhow::{Context, Result};
+
+/// How the axes of a SpatialRef map to the x / y of the coordinates, see OSRSetAxisMappingStrategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AxisMappingStrategy {
+    /// Axis order of the authority, i.e. latitude, longitude for EPSG:4326
+    AuthorityCompliant,
+    /// x is easting / longitude, y is northing / latitude
+    TraditionalGisOrder,
+    Custom,
+}
+
+impl AxisMappingStrategy {
+    fn to_c(self) -> OSRAxisMappingStrategy::Type {
+        match self {
+            AxisMappingStrategy::AuthorityCompliant => OSRAxisMappingStrategy::OAMS_AUTHORITY_COMPLIANT,
+            AxisMappingStrategy::TraditionalGisOrder => OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER,
+            AxisMappingStrategy::Custom => OSRAxisMappingStrategy::OAMS_CUSTOM,
+        }
+    }
+
+    fn from_c(strategy: OSRAxisMappingStrategy::Type) -> AxisMappingStrategy {
+        match strategy {
+            OSRAxisMappingStrategy::OAMS_AUTHORITY_COMPLIANT => AxisMappingStrategy::AuthorityCompliant,
+            OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER => AxisMappingStrategy::TraditionalGisOrder,
+            _ => AxisMappingStrategy::Custom,
+        }
+    }
+}
+
+/// How strictly `SpatialRef::is_same_with_options` compares, the CRITERION option of OSRIsSameEx
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatumCriterion {
+    /// Same definition, names and metadata included
+    Strict,
+    /// Equivalent definitions, names may differ.  What `==` uses
+    Equivalent,
+    /// Equivalent, except that geographic CRS in latitude, longitude and longitude, latitude
+    /// order are considered the same
+    EquivalentExceptAxisOrderGeogcrs,
+}
+
+impl DatumCriterion {
+    fn to_option(self) -> &'static str {
+        match self {
+            DatumCriterion::Strict => "CRITERION=STRICT",
+            DatumCriterion::Equivalent => "CRITERION=EQUIVALENT",
+            DatumCriterion::EquivalentExceptAxisOrderGeogcrs => "CRITERION=EQUIVALENT_EXCEPT_AXIS_ORDER_GEOGCRS",
+        }
+    }
+}
 
 pub struct CoordTransform {
     inner: OGRCoordinateTransformationH,
     from: String,
     to: String,
+    pinned_operation: Option<String>,
 }
 
 impl Drop for CoordTransform {
@@ -49,9 +104,53 @@ impl CoordTransform {
             inner: c_obj,
             from: sp_ref1.authority().or_else(|_| sp_ref1.to_proj4())?,
             to: sp_ref2.authority().or_else(|_| sp_ref2.to_proj4())?,
+            pinned_operation: None,
+        })
+    }
+
+    /// Transform from `from` to EPSG:`epsg` in the traditional GIS axis order (x = longitude / easting)
+    pub fn to_epsg(from: &SpatialRef, epsg: u32) -> Result<CoordTransform> {
+        CoordTransform::new(from, &SpatialRef::from_epsg(epsg)?)
+    }
+
+    /// Transform between two EPSG codes, both in the traditional GIS axis order
+    pub fn between_epsg(from: u32, to: u32) -> Result<CoordTransform> {
+        CoordTransform::new(&SpatialRef::from_epsg(from)?, &SpatialRef::from_epsg(to)?)
+    }
+
+    /// Like `new` but forces the coordinate operation (a PROJ string, a PROJ pipeline or e.g.
+    /// "urn:ogc:def:coordinateOperation:EPSG::1676") instead of letting PROJ pick one,
+    /// so results are reproducible when several candidate transformations exist between two datums
+    pub fn new_with_operation(sp_ref1: &SpatialRef, sp_ref2: &SpatialRef, operation: &str) -> Result<CoordTransform> {
+        let c_operation = CString::new(operation)?;
+        let c_options = unsafe { gdal_sys::OCTNewCoordinateTransformationOptions() };
+        if c_options.is_null() {
+            Err(_last_null_pointer_err("OCTNewCoordinateTransformationOptions"))?;
+        }
+        let c_obj = unsafe {
+            gdal_sys::OCTCoordinateTransformationOptionsSetOperation(c_options, c_operation.as_ptr(), 0);
+            let c_obj = gdal_sys::OCTNewCoordinateTransformationEx(sp_ref1.c_spatial_ref, sp_ref2.c_spatial_ref, c_options);
+            gdal_sys::OCTDestroyCoordinateTransformationOptions(c_options);
+            c_obj
+        };
+        if c_obj.is_null() {
+            Err(_last_null_pointer_err("OCTNewCoordinateTransformationEx"))?;
+        }
+        Ok(CoordTransform {
+            inner: c_obj,
+            from: sp_ref1.authority().or_else(|_| sp_ref1.to_proj4())?,
+            to: sp_ref2.authority().or_else(|_| sp_ref2.to_proj4())?,
+            pinned_operation: Some(operation.to_string()),
         })
     }
 
+    /// The operation passed to `new_with_operation`, `None` for transforms created with `new`.
+    /// This is not the operation PROJ selected on its own: the GDAL C API doesn't expose that
+    /// (nor its accuracy), so pin the operation when it has to be recorded
+    pub fn pinned_operation(&self) -> Option<&str> {
+        self.pinned_operation.as_deref()
+    }
+
     pub fn transform_point(&self, xy: &[f64; 2]) -> Result<[f64; 2]> {
         let mut x = [xy[0]];
         let mut y = [xy[1]];
@@ -96,6 +195,42 @@ impl CoordTransform {
         }
     }
 
+    /// Same as `transform_coords` but makes one FFI call per window of `chunk` points, so huge
+    /// reprojections don't hand PROJ everything at once.  On failure the error says which chunk failed;
+    /// the chunks before it are already transformed
+    pub fn transform_coords_chunked(&self, x: &mut [f64], y: &mut [f64], z: &mut [f64], chunk: usize) -> Result<()> {
+        assert!(chunk > 0);
+        assert_eq!(x.len(), y.len());
+        assert_eq!(x.len(), z.len());
+
+        for (chunk_index, ((x, y), z)) in x.chunks_mut(chunk)
+            .zip(y.chunks_mut(chunk))
+            .zip(z.chunks_mut(chunk))
+            .enumerate()
+        {
+            self.transform_coords(x, y, z)
+                .with_context(|| format!("Transforming chunk {} of {} points", chunk_index, chunk))?;
+        }
+
+        Ok(())
+    }
+
+    /// Transform each point with self then back with `inverse`, returns the distance
+    /// between each original point and its round trip, in source units.
+    /// Useful to check a transformation pipeline is accurate enough over a region.
+    pub fn round_trip_error(&self, inverse: &CoordTransform, pts: &[[f64; 2]]) -> Result<Vec<f64>> {
+        let mut xs: Vec<f64> = pts.iter().map(|p| p[0]).collect();
+        let mut ys: Vec<f64> = pts.iter().map(|p| p[1]).collect();
+        let mut zs = vec![0.0; pts.len()];
+
+        self.transform_coords(&mut xs, &mut ys, &mut zs)?;
+        inverse.transform_coords(&mut xs, &mut ys, &mut zs)?;
+
+        Ok(pts.iter().zip(xs.iter().zip(ys.iter()))
+            .map(|(p, (x, y))| ((p[0] - x).powi(2) + (p[1] - y).powi(2)).sqrt())
+            .collect())
+    }
+
     #[deprecated(since = "0.3.1", note = "use `transform_coords` instead")]
     pub fn transform_coord(&self, x: &mut [f64], y: &mut [f64], z: &mut [f64]) {
         self.transform_coords(x, y, z)
@@ -107,7 +242,6 @@ impl CoordTransform {
     }
 }
 
-#[derive(Debug)]
 pub struct SpatialRef {
     pub(crate) c_spatial_ref: OGRSpatialReferenceH
 }
@@ -128,12 +262,46 @@ impl Clone for SpatialRef {
     }
 }
 
+/// Prints the authority (e.g. `EPSG:2056`) when the SRS has one, the proj4 string otherwise
+impl fmt::Display for SpatialRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.authority().or_else(|_| self.to_proj4()) {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => write!(f, "<unknown spatial reference>"),
+        }
+    }
+}
+
+impl fmt::Debug for SpatialRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SpatialRef({})", self)
+    }
+}
+
 impl PartialEq for SpatialRef {
     fn eq(&self, other: &SpatialRef) -> bool {
         unsafe { gdal_sys::OSRIsSame(self.c_spatial_ref, other.c_spatial_ref) == 1 }
     }
 }
 
+impl Eq for SpatialRef {}
+
+/// Hashes the authority (e.g. `EPSG:2056`) when the SRS has one, its WKT2 otherwise, so two
+/// references with the same authority hash equal whatever their axis mapping or origin.
+///
+/// `PartialEq` is `OSRIsSame`, which is not transitive and also matches definitions that differ
+/// textually, so `Eq` is only approximately an equivalence: an SRS without an authority, like
+/// one built from a proj4 string, can equal its EPSG counterpart and still hash differently.
+/// Call `auto_identify_epsg` on such references before using them as keys
+impl Hash for SpatialRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.authority() {
+            Ok(authority) => authority.hash(state),
+            Err(_) => self.to_wkt2().unwrap_or_default().hash(state),
+        }
+    }
+}
+
 impl SpatialRef {
     pub fn new() -> Result<SpatialRef> {
         let c_obj = unsafe { gdal_sys::OSRNewSpatialReference(ptr::null()) };
@@ -152,7 +320,7 @@ impl SpatialRef {
             unsafe { gdal_sys::OSRSetFromUserInput(c_obj, CString::new(definition)?.as_ptr()) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRSetFromUserInput",
             })?;
         }
@@ -178,7 +346,7 @@ impl SpatialRef {
         let rv = unsafe { gdal_sys::OSRImportFromEPSG(c_obj, epsg_code as c_int) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRImportFromEPSG",
             })?
         } else {
@@ -188,6 +356,27 @@ impl SpatialRef {
         }
     }
 
+    /// Like `from_epsg` but keeps the axis order of the authority, i.e. latitude, longitude
+    /// for EPSG:4326.  `from_epsg` forces the traditional GIS order (x = easting / longitude)
+    pub fn from_epsga(epsg_code: u32) -> Result<SpatialRef> {
+        let c_obj = unsafe { gdal_sys::OSRNewSpatialReference(ptr::null()) };
+        if c_obj.is_null() {
+            Err(_last_null_pointer_err("OSRNewSpatialReference"))?;
+        }
+        let r = SpatialRef{c_spatial_ref: c_obj};
+        let rv = unsafe { gdal_sys::OSRImportFromEPSGA(r.c_spatial_ref, epsg_code as c_int) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OSRImportFromEPSGA",
+            })?
+        } else {
+            let mut r = r;
+            r.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_AUTHORITY_COMPLIANT);
+            Ok(r)
+        }
+    }
+
     pub fn from_proj4(proj4_string: &str) -> Result<SpatialRef> {
         let c_str = CString::new(proj4_string)?;
         let null_ptr = ptr::null_mut();
@@ -195,7 +384,7 @@ impl SpatialRef {
         let rv = unsafe { gdal_sys::OSRImportFromProj4(c_obj, c_str.as_ptr()) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRImportFromProj4",
             })?
         } else {
@@ -213,7 +402,7 @@ impl SpatialRef {
         let rv = unsafe { gdal_sys::OSRImportFromESRI(c_obj, ptrs.as_mut_ptr()) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRImportFromESRI",
             })?
         } else {
@@ -237,7 +426,7 @@ impl SpatialRef {
         let rv = unsafe { gdal_sys::OSRExportToWkt(self.c_spatial_ref, &mut c_wkt) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRExportToWkt",
             })?
         } else {
@@ -245,23 +434,53 @@ impl SpatialRef {
         }
     }
 
+    /// Single line WKT2 (2018), the canonical form used to hash references without an authority
+    pub fn to_wkt2(&self) -> Result<String> {
+        let c_options = [
+            CString::new("FORMAT=WKT2_2018")?,
+            CString::new("MULTILINE=NO")?,
+        ];
+        let c_option_ptrs = [c_options[0].as_ptr(), c_options[1].as_ptr(), ptr::null()];
+        let mut c_wkt = ptr::null_mut();
+        let rv = unsafe { gdal_sys::OSRExportToWktEx(self.c_spatial_ref, &mut c_wkt, c_option_ptrs.as_ptr()) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OSRExportToWktEx",
+            })?;
+        }
+        let wkt = _string(c_wkt);
+        unsafe { gdal_sys::VSIFree(c_wkt as *mut libc::c_void) };
+        Ok(wkt)
+    }
+
     pub fn morph_to_esri(&self) -> Result<()> {
         let rv = unsafe { gdal_sys::OSRMorphToESRI(self.c_spatial_ref) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRMorphToESRI",
             })?;
         }
         Ok(())
     }
 
+    /// Writes the ESRI flavoured WKT shapefiles expect to `path` (the `.prj` next to the `.shp`).
+    /// Works on a copy, self is not morphed
+    pub fn write_prj(&self, path: &Path) -> Result<()> {
+        let esri = self.clone();
+        esri.morph_to_esri()?;
+        let wkt = esri.to_wkt()?;
+        fs::write(path, wkt).with_context(|| format!("Writing prj file {:?}", path))?;
+        Ok(())
+    }
+
     pub fn to_pretty_wkt(&self) -> Result<String> {
         let mut c_wkt = ptr::null_mut();
         let rv = unsafe { gdal_sys::OSRExportToPrettyWkt(self.c_spatial_ref, &mut c_wkt, false as c_int) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRExportToPrettyWkt",
             })?
         } else {
@@ -274,7 +493,7 @@ impl SpatialRef {
         let rv = unsafe { gdal_sys::OSRExportToXML(self.c_spatial_ref, &mut c_raw_xml, ptr::null()) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRExportToXML",
             })?
         } else {
@@ -287,7 +506,7 @@ impl SpatialRef {
         let rv = unsafe { gdal_sys::OSRExportToProj4(self.c_spatial_ref, &mut c_proj4str) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRExportToProj4",
             }
             .into())
@@ -315,7 +534,7 @@ impl SpatialRef {
         match epsg {
             Ok(n) => Ok(n),
             Err(_) => Err(ErrorKind::OgrError {
-                err: OGRErr::OGRERR_UNSUPPORTED_SRS,
+                err: OgrError::UnsupportedSrs,
                 method_name: "OSRGetAuthorityCode",
             })?,
         }
@@ -339,7 +558,7 @@ impl SpatialRef {
         let rv = unsafe { gdal_sys::OSRAutoIdentifyEPSG(self.c_spatial_ref) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OSRAutoIdentifyEPSG",
             })?
         } else {
@@ -359,5 +578,103 @@ impl SpatialRef {
         unsafe { gdal_sys::OSRGetAxisMappingStrategy(self.c_spatial_ref) }
     }
 
+    /// Same as `set_axis_mapping_strategy` without going through gdal_sys
+    pub fn set_axis_mapping(&mut self, strategy: AxisMappingStrategy) {
+        self.set_axis_mapping_strategy(strategy.to_c());
+    }
+
+    pub fn axis_mapping(&self) -> AxisMappingStrategy {
+        AxisMappingStrategy::from_c(self.get_axis_mapping_strategy())
+    }
+
+    /// Lambert azimuthal equal-area projection centered on the centroid of `bounds`
+    /// (`[min_x, min_y, max_x, max_y]` in this SRS), on the same ellipsoid as this SRS.
+    /// Useful to compute areas of features that cover a limited region.
+    pub fn suggest_equal_area(&self, bounds: [f64; 4]) -> Result<SpatialRef> {
+        let c_geog = unsafe { gdal_sys::OSRCloneGeogCS(self.c_spatial_ref) };
+        if c_geog.is_null() {
+            Err(_last_null_pointer_err("OSRCloneGeogCS"))?;
+        }
+        let mut geog = SpatialRef{c_spatial_ref: c_geog};
+        geog.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+
+        let center = [(bounds[0] + bounds[2]) / 2.0, (bounds[1] + bounds[3]) / 2.0];
+        let lon_lat = if self.is_geographic() {
+            center
+        } else {
+            CoordTransform::new(self, &geog)?.transform_point(&center)?
+        };
+
+        let semi_major = geog.semi_major()?;
+        let inv_flattening = geog.inverse_flattening()?;
+
+        // an inverse flattening of 0 means a sphere
+        let ellipsoid = if inv_flattening == 0.0 {
+            format!("+R={}", semi_major)
+        } else {
+            format!("+a={} +rf={}", semi_major, inv_flattening)
+        };
+
+        SpatialRef::from_proj4(&format!(
+            "+proj=laea +lat_0={} +lon_0={} +x_0=0 +y_0=0 {} +units=m +no_defs",
+            lon_lat[1], lon_lat[0], ellipsoid
+        ))
+    }
+
+    /// Semi-major axis of the ellipsoid in meters
+    pub fn semi_major(&self) -> Result<f64> {
+        let mut rv = OGRErr::OGRERR_NONE;
+        let semi_major = unsafe { gdal_sys::OSRGetSemiMajor(self.c_spatial_ref, &mut rv) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OSRGetSemiMajor",
+            })?
+        }
+        Ok(semi_major)
+    }
+
+    /// Inverse flattening of the ellipsoid, 0 for a sphere
+    pub fn inverse_flattening(&self) -> Result<f64> {
+        let mut rv = OGRErr::OGRERR_NONE;
+        let inv_flattening = unsafe { gdal_sys::OSRGetInvFlattening(self.c_spatial_ref, &mut rv) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OSRGetInvFlattening",
+            })?
+        }
+        Ok(inv_flattening)
+    }
+
+    /// Size of the angular unit in radians, i.e. pi / 180 for degrees
+    pub fn angular_units(&self) -> f64 {
+        unsafe { gdal_sys::OSRGetAngularUnits(self.c_spatial_ref, ptr::null_mut()) }
+    }
+
+    /// True when the coordinates are latitude, longitude: the axis order of the authority is
+    /// used and the authority puts latitude first (EPSG:4326 from `from_epsga`)
+    pub fn is_lat_long_order(&self) -> bool {
+        self.axis_mapping() == AxisMappingStrategy::AuthorityCompliant
+            && unsafe { gdal_sys::OSREPSGTreatsAsLatLong(self.c_spatial_ref) } == 1
+    }
+
+    /// `==` with control over the comparison.  With `ignore_axis` the axis mapping strategies
+    /// of the two references (see `set_axis_mapping_strategy`) are not compared
+    pub fn is_same_with_options(&self, other: &SpatialRef, ignore_axis: bool, criterion: DatumCriterion) -> bool {
+        let c_criterion = CString::new(criterion.to_option()).unwrap();
+        let c_ignore_axis = CString::new(format!(
+            "IGNORE_DATA_AXIS_TO_SRS_AXIS_MAPPING={}",
+            if ignore_axis { "YES" } else { "NO" }
+        )).unwrap();
+        let c_options = [c_criterion.as_ptr(), c_ignore_axis.as_ptr(), ptr::null()];
+
+        unsafe { gdal_sys::OSRIsSameEx(self.c_spatial_ref, other.c_spatial_ref, c_options.as_ptr()) == 1 }
+    }
+
+    pub fn is_geographic(&self) -> bool {
+        unsafe { gdal_sys::OSRIsGeographic(self.c_spatial_ref) != 0 }
+    }
+
 
 }
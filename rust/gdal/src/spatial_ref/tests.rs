@@ -15,7 +15,7 @@ GNU General Public License for more details.
 You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
-use super::srs::{CoordTransform, SpatialRef};
+use super::srs::{AxisMappingStrategy, CoordTransform, DatumCriterion, SpatialRef};
 use crate::assert_almost_eq;
 use crate::vector::Geometry;
 
@@ -39,10 +39,6 @@ fn from_proj4_to_wkt() {
         "+proj=laea +lat_0=52 +lon_0=10 +x_0=4321000 +y_0=3210000 +ellps=GRS80 +units=m +no_defs",
     )
     .unwrap();
-    // TODO: handle proj changes on lib level
-    //#[cfg(not(feature = "gdal_3_0"))]
-    //assert_eq!(spatial_ref.to_wkt().unwrap(), "PROJCS[\"unnamed\",GEOGCS[\"GRS 1980(IUGG, 1980)\",DATUM[\"unknown\",SPHEROID[\"GRS80\",6378137,298.257222101]],PRIMEM[\"Greenwich\",0],UNIT[\"degree\",0.0174532925199433]],PROJECTION[\"Lambert_Azimuthal_Equal_Area\"],PARAMETER[\"latitude_of_center\",52],PARAMETER[\"longitude_of_center\",10],PARAMETER[\"false_easting\",4321000],PARAMETER[\"false_northing\",3210000],UNIT[\"Meter\",1]]");
-    //#[cfg(feature = "gdal_3_0")]
     assert_eq!(spatial_ref.to_wkt().unwrap(), "PROJCS[\"unknown\",GEOGCS[\"unknown\",DATUM[\"Unknown based on GRS80 ellipsoid\",SPHEROID[\"GRS 1980\",6378137,298.257222101,AUTHORITY[\"EPSG\",\"7019\"]]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",\"8901\"]],UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]]],PROJECTION[\"Lambert_Azimuthal_Equal_Area\"],PARAMETER[\"latitude_of_center\",52],PARAMETER[\"longitude_of_center\",10],PARAMETER[\"false_easting\",4321000],PARAMETER[\"false_northing\",3210000],UNIT[\"metre\",1,AUTHORITY[\"EPSG\",\"9001\"]],AXIS[\"Easting\",EAST],AXIS[\"Northing\",NORTH]]");
 }
 
@@ -50,10 +46,6 @@ fn from_proj4_to_wkt() {
 fn from_epsg_to_wkt_proj4() {
     let spatial_ref = SpatialRef::from_epsg(4326).unwrap();
     let wkt = spatial_ref.to_wkt().unwrap();
-    // TODO: handle proj changes on lib level
-    //#[cfg(not(feature = "gdal_3_0"))]
-    //assert_eq!("GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563,AUTHORITY[\"EPSG\",\"7030\"]],AUTHORITY[\"EPSG\",\"6326\"]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",\"8901\"]],UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]],AUTHORITY[\"EPSG\",\"4326\"]]", wkt);
-    //#[cfg(feature = "gdal_3_0")]
     assert_eq!("GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563,AUTHORITY[\"EPSG\",\"7030\"]],AUTHORITY[\"EPSG\",\"6326\"]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",\"8901\"]],UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]],AXIS[\"Latitude\",NORTH],AXIS[\"Longitude\",EAST],AUTHORITY[\"EPSG\",\"4326\"]]", wkt);
     let proj4string = spatial_ref.to_proj4().unwrap();
     assert_eq!("+proj=longlat +datum=WGS84 +no_defs", proj4string.trim());
@@ -80,6 +72,20 @@ fn comparison() {
    // assert_eq!(true, spatial_ref5 == spatial_ref4);
 }
 
+#[test]
+fn comparison_with_options() {
+    let authority_order = SpatialRef::from_epsga(4326).unwrap();
+    // from_epsg uses the traditional GIS order
+    let gis_order = SpatialRef::from_epsg(4326).unwrap();
+
+    assert!(authority_order.is_same_with_options(&authority_order, false, DatumCriterion::Strict));
+    assert!(authority_order.is_same_with_options(&gis_order, true, DatumCriterion::Equivalent));
+    assert!(!authority_order.is_same_with_options(&gis_order, false, DatumCriterion::Equivalent));
+
+    let other = SpatialRef::from_epsg(3025).unwrap();
+    assert!(!authority_order.is_same_with_options(&other, true, DatumCriterion::EquivalentExceptAxisOrderGeogcrs));
+}
+
 #[test]
 fn transform_coordinates() {
     let mut spatial_ref1 = SpatialRef::from_wkt("GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563,AUTHORITY[\"EPSG\",7030]],TOWGS84[0,0,0,0,0,0,0],AUTHORITY[\"EPSG\",6326]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",8901]],UNIT[\"DMSH\",0.0174532925199433,AUTHORITY[\"EPSG\",9108]],AXIS[\"Lat\",NORTH],AXIS[\"Long\",EAST],AUTHORITY[\"EPSG\",4326]]").unwrap();
@@ -103,11 +109,24 @@ fn transform_coordinates() {
     assert_almost_eq(ys[0], 1716062.1916192223);
 }
 
+#[test]
+fn round_trip_error() {
+    let mut wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    let mut lv95 = SpatialRef::from_epsg(2056).unwrap();
+    wgs84.set_axis_mapping_strategy(gdal_sys::OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+    lv95.set_axis_mapping_strategy(gdal_sys::OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+
+    let forward = CoordTransform::new(&lv95, &wgs84).unwrap();
+    let inverse = CoordTransform::new(&wgs84, &lv95).unwrap();
+    let errors = forward
+        .round_trip_error(&inverse, &[[2600000.0, 1200000.0], [2700000.0, 1250000.0]])
+        .unwrap();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|e| *e < 0.01));
+}
+
 #[test]
 fn transform_ogr_geometry() {
-    //let expected_value = "POLYGON ((5509543.150809700600803 1716062.191619219258428,5467122.000330002978444 1980151.204280239529908,5623571.028492723591626 2010213.310253676958382,5671834.921544363722205 1746968.078280254499987,5509543.150809700600803 1716062.191619219258428))";
-    //let expected_value = "POLYGON ((5509543.15080969966948 1716062.191619222285226,5467122.000330002047122 1980151.204280242323875,5623571.028492721728981 2010213.31025367998518,5671834.921544362790883 1746968.078280256595463,5509543.15080969966948 1716062.191619222285226))";
-    let expected_value = "POLYGON ((5509543.1508097 1716062.19161922,5467122.00033 1980151.20428024,5623571.02849272 2010213.31025368,5671834.92154436 1746968.07828026,5509543.1508097 1716062.19161922))";
     let mut geom = Geometry::from_wkt(
         "POLYGON((23.43 37.58, 23.43 40.0, 25.29 40.0, 25.29 37.58, 23.43 37.58))",
     )
@@ -128,7 +147,17 @@ fn transform_ogr_geometry() {
 
     let htransform = CoordTransform::new(&spatial_ref2, &spatial_ref1).unwrap();
     geom.transform_inplace(&htransform).unwrap();
-    assert_eq!(expected_value, geom.wkt().unwrap());
+
+    // the last digits change with the PROJ version, compare at cm precision
+    let context = geos::SimpleContextHandle::new();
+    let geos_geom = geos::WKBReader::new(&context)
+        .unwrap()
+        .read_wkb(&geom.ewkb_bytes_raw().unwrap())
+        .unwrap();
+    assert_eq!(
+        geos_geom.to_wkt_precision(2).unwrap(),
+        "POLYGON ((5509543.15 1716062.19, 5467122 1980151.2, 5623571.03 2010213.31, 5671834.92 1746968.08, 5509543.15 1716062.19))"
+    );
 }
 
 #[test]
@@ -154,6 +183,260 @@ fn authority() {
     assert!(spatial_ref.authority().is_err());
 }
 
+#[test]
+fn display() {
+    let spatial_ref = SpatialRef::from_epsg(2056).unwrap();
+    assert_eq!(format!("{}", spatial_ref), "EPSG:2056");
+    let spatial_ref = SpatialRef::from_proj4("+proj=longlat +datum=WGS84 +no_defs").unwrap();
+    assert!(format!("{}", spatial_ref).contains("+proj=longlat"));
+}
+
+#[test]
+fn axis_mapping() {
+    let mut wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    assert_eq!(wgs84.axis_mapping(), AxisMappingStrategy::TraditionalGisOrder);
+
+    wgs84.set_axis_mapping(AxisMappingStrategy::AuthorityCompliant);
+    assert_eq!(wgs84.axis_mapping(), AxisMappingStrategy::AuthorityCompliant);
+    assert_eq!(
+        wgs84.get_axis_mapping_strategy(),
+        gdal_sys::OSRAxisMappingStrategy::OAMS_AUTHORITY_COMPLIANT
+    );
+}
+
+#[test]
+fn from_epsga() {
+    let wgs84 = SpatialRef::from_epsga(4326).unwrap();
+    assert_eq!(wgs84.axis_mapping(), AxisMappingStrategy::AuthorityCompliant);
+    let lv95 = SpatialRef::from_epsg(2056).unwrap();
+    let transform = CoordTransform::new(&lv95, &wgs84).unwrap();
+    let [lat, lon] = transform.transform_point(&[2600000.0, 1200000.0]).unwrap();
+    assert!(lat > 46.0 && lat < 47.5);
+    assert!(lon > 7.0 && lon < 8.0);
+}
+
+#[test]
+fn transform_with_operation() {
+    let lv03 = SpatialRef::from_epsg(21781).unwrap();
+    let lv95 = SpatialRef::from_epsg(2056).unwrap();
+    let transform = CoordTransform::new(&lv03, &lv95).unwrap();
+    assert!(transform.pinned_operation().is_none());
+
+    let pipeline = "+proj=affine +xoff=2000000 +yoff=1000000";
+    let transform = CoordTransform::new_with_operation(&lv03, &lv95, pipeline).unwrap();
+    assert_eq!(transform.pinned_operation(), Some(pipeline));
+    let [x, y] = transform.transform_point(&[600000.0, 200000.0]).unwrap();
+    assert_almost_eq(x, 2600000.0);
+    assert_almost_eq(y, 1200000.0);
+}
+
+#[test]
+fn transform_coords_chunked() {
+    let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    let dhd_2 = SpatialRef::from_epsg(31462).unwrap();
+    let trafo = CoordTransform::new(&wgs84, &dhd_2).unwrap();
+
+    let mut x = [6.5, 6.6, 6.7];
+    let mut y = [50.5, 50.6, 50.7];
+    let mut z = [0.0; 3];
+    let (mut x_ref, mut y_ref, mut z_ref) = (x, y, z);
+    trafo.transform_coords_chunked(&mut x, &mut y, &mut z, 2).unwrap();
+    trafo.transform_coords(&mut x_ref, &mut y_ref, &mut z_ref).unwrap();
+    assert_eq!(x, x_ref);
+    assert_eq!(y, y_ref);
+
+    let mut x = [6.5, 6.6, 1979105.06];
+    let mut y = [50.5, 50.6, 5694052.67];
+    let mut z = [0.0; 3];
+    let err = trafo.transform_coords_chunked(&mut x, &mut y, &mut z, 2).unwrap_err();
+    assert!(err.to_string().contains("chunk 1"));
+}
+
+#[test]
+fn transform_between() {
+    let lv03 = SpatialRef::from_epsg(21781).unwrap();
+    let lv95 = SpatialRef::from_epsg(2056).unwrap();
+    let geom = Geometry::from_wkt("POINT (600000 200000)").unwrap();
+
+    let expected = geom.transform(&CoordTransform::new(&lv03, &lv95).unwrap()).unwrap();
+    let transformed = geom.transform_between(&lv03, &lv95).unwrap();
+    assert!(transformed.equals_with_tolerance(&expected, 1e-9));
+    let [x, y] = transformed.get_point(0);
+    assert!((x - 2600000.0).abs() < 1.0);
+    assert!((y - 1200000.0).abs() < 1.0);
+}
+
+#[test]
+fn swap_xy() {
+    // with the authority axis order EPSG:4326 gives latitude, longitude
+    let lv95 = SpatialRef::from_epsg(2056).unwrap();
+    let wgs84_lat_long = SpatialRef::from_epsga(4326).unwrap();
+    assert!(wgs84_lat_long.is_lat_long_order());
+    assert!(!SpatialRef::from_epsg(4326).unwrap().is_lat_long_order());
+
+    let geom = Geometry::from_wkt("POINT (2600000 1200000)").unwrap();
+    let mut lat_long = geom.transform_between(&lv95, &wgs84_lat_long).unwrap();
+    let [lat, lon] = lat_long.get_point(0);
+    assert!((lat - 46.95).abs() < 0.01);
+    assert!((lon - 7.44).abs() < 0.01);
+
+    lat_long.swap_xy();
+    assert_eq!(lat_long.get_point(0), [lon, lat]);
+    let expected = geom.transform_between(&lv95, &SpatialRef::from_epsg(4326).unwrap()).unwrap();
+    assert!(lat_long.equals_with_tolerance(&expected, 1e-9));
+}
+
+#[test]
+fn transform_epsg_shortcuts() {
+    let lv95_to_wgs84 = CoordTransform::between_epsg(2056, 4326).unwrap();
+    let [lon, lat] = lv95_to_wgs84.transform_point(&[2600000.0, 1200000.0]).unwrap();
+    assert!((lon - 7.44).abs() < 0.01);
+    assert!((lat - 46.95).abs() < 0.01);
+
+    let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    let wgs84_to_lv95 = CoordTransform::to_epsg(&wgs84, 2056).unwrap();
+    let [x, y] = wgs84_to_lv95.transform_point(&[lon, lat]).unwrap();
+    assert!((x - 2600000.0).abs() < 0.01);
+    assert!((y - 1200000.0).abs() < 0.01);
+
+    assert!(CoordTransform::between_epsg(2056, 999999).is_err());
+}
+
+#[test]
+fn write_prj() {
+    let path = std::env::temp_dir().join("gdal_test_write_prj.prj");
+    let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    wgs84.write_prj(&path).unwrap();
+
+    let prj = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(prj.starts_with("GEOGCS[\"GCS_WGS_1984\""));
+    // the SpatialRef itself is not morphed
+    assert_eq!(wgs84.auth_code().unwrap(), 4326);
+
+    let missing_dir = std::env::temp_dir().join("no_such_dir").join("out.prj");
+    assert!(wgs84.write_prj(&missing_dir).is_err());
+}
+
+#[test]
+fn unknown_epsg_error() {
+    use crate::errors::{ErrorKind, OgrError};
+
+    let err = SpatialRef::from_epsg(1).unwrap_err();
+    match err.downcast_ref::<ErrorKind>() {
+        Some(ErrorKind::OgrError { err: OgrError::UnsupportedSrs, method_name }) => {
+            assert_eq!(*method_name, "OSRImportFromEPSG")
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[test]
+fn failing_transform_keeps_source() {
+    let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    let dhd_2 = SpatialRef::from_epsg(31462).unwrap();
+    let trafo = CoordTransform::new(&wgs84, &dhd_2).unwrap();
+
+    let wkt = "LINESTRING (6.5 50.5,1979105.06 5694052.67)";
+    let geom = Geometry::from_wkt(wkt).unwrap();
+    assert!(geom.transform(&trafo).is_err());
+    assert_eq!(geom.wkt().unwrap(), wkt);
+}
+
+#[test]
+fn suggest_equal_area() {
+    let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    let laea = wgs84.suggest_equal_area([6.0, 46.0, 10.0, 48.0]).unwrap();
+    let proj4 = laea.to_proj4().unwrap();
+    assert!(proj4.contains("+proj=laea"));
+    assert!(proj4.contains("+lat_0=47"));
+    assert!(proj4.contains("+lon_0=8"));
+
+    // the center of the extent maps to the origin
+    let trafo = CoordTransform::new(&wgs84, &laea).unwrap();
+    let xy = trafo.transform_point(&[8.0, 47.0]).unwrap();
+    assert_almost_eq(xy[0], 0.0);
+    assert_almost_eq(xy[1], 0.0);
+
+    // projected input: centroid is first brought back to lon / lat
+    let utm = SpatialRef::from_epsg(32632).unwrap();
+    let laea = utm.suggest_equal_area([400000.0, 5200000.0, 600000.0, 5300000.0]).unwrap();
+    assert!(laea.to_proj4().unwrap().contains("+lon_0=9"));
+}
+
+#[test]
+fn hash_map_key() {
+    use std::collections::HashMap;
+
+    let wkt = SpatialRef::from_epsg(4326).unwrap().to_wkt().unwrap();
+    let mut names = HashMap::new();
+    names.insert(SpatialRef::from_epsg(4326).unwrap(), "wgs84");
+    names.insert(SpatialRef::from_epsg(2056).unwrap(), "lv95");
+    names.insert(SpatialRef::from_epsg(32632).unwrap(), "utm32");
+    names.insert(SpatialRef::from_epsg(32633).unwrap(), "utm33");
+    assert_eq!(names.len(), 4);
+
+    assert_eq!(names.get(&SpatialRef::from_wkt(&wkt).unwrap()), Some(&"wgs84"));
+    names.insert(SpatialRef::from_wkt(&wkt).unwrap(), "wgs84 from wkt");
+    assert_eq!(names.len(), 4);
+    assert_eq!(names.get(&SpatialRef::from_epsg(4326).unwrap()), Some(&"wgs84 from wkt"));
+    assert_eq!(names.get(&SpatialRef::from_epsg(32633).unwrap()), Some(&"utm33"));
+
+    // no authority: hashed by its WKT2, identified it hashes like the EPSG SRS
+    let proj4 = "+proj=utm +zone=32 +datum=WGS84 +units=m +no_defs";
+    let mut utm32 = SpatialRef::from_proj4(proj4).unwrap();
+    let mut by_wkt2 = HashMap::new();
+    by_wkt2.insert(utm32.clone(), "utm32 from proj4");
+    assert_eq!(by_wkt2.get(&SpatialRef::from_proj4(proj4).unwrap()), Some(&"utm32 from proj4"));
+    utm32.auto_identify_epsg().unwrap();
+    assert_eq!(names.get(&utm32), Some(&"utm32"));
+}
+
+#[test]
+fn to_wkt2() {
+    let wkt2 = SpatialRef::from_epsg(2056).unwrap().to_wkt2().unwrap();
+    assert!(wkt2.starts_with("PROJCRS["));
+    assert!(!wkt2.contains('\n'));
+}
+
+#[test]
+fn hash_projected_on_same_ellipsoid() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let hash = |srs: &SpatialRef| {
+        let mut hasher = DefaultHasher::new();
+        srs.hash(&mut hasher);
+        hasher.finish()
+    };
+    let utm32 = SpatialRef::from_epsg(32632).unwrap();
+    let utm33 = SpatialRef::from_epsg(32633).unwrap();
+    assert_ne!(hash(&utm32), hash(&utm33));
+    assert_eq!(hash(&utm32), hash(&SpatialRef::from_epsg(32632).unwrap()));
+}
+
+#[test]
+fn geodesic_length() {
+    let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+
+    // one degree along the equator and along a meridian on WGS84
+    let equator = Geometry::from_wkt("LINESTRING (0 0, 0.5 0, 1 0)").unwrap();
+    assert!((equator.geodesic_length(&wgs84).unwrap() - 111_319.491).abs() < 0.01);
+    let meridian = Geometry::from_wkt("LINESTRING (0 0, 0 1)").unwrap();
+    assert!((meridian.geodesic_length(&wgs84).unwrap() - 110_574.389).abs() < 0.01);
+
+    // lat, long coordinates with the authority axis order
+    let wgs84_lat_long = SpatialRef::from_epsga(4326).unwrap();
+    let meridian = Geometry::from_wkt("LINESTRING (0 0, 1 0)").unwrap();
+    assert!((meridian.geodesic_length(&wgs84_lat_long).unwrap() - 110_574.389).abs() < 0.01);
+
+    let square = Geometry::from_wkt("POLYGON ((0 0, 1 0, 1 1, 0 1, 0 0))").unwrap();
+    let perimeter = square.geodesic_length(&wgs84).unwrap();
+    assert!(perimeter > 4.0 * 110_000.0 && perimeter < 4.0 * 111_400.0);
+
+    assert!(equator.geodesic_length(&SpatialRef::from_epsg(2056).unwrap()).is_err());
+}
+
 #[test]
 fn failing_transformation() {
     let wgs84 = SpatialRef::from_epsg(4326).unwrap();
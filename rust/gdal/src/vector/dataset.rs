@@ -18,14 +18,15 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 use crate::gdal_major_object::MajorObject;
 use crate::metadata::Metadata;
 use crate::spatial_ref::SpatialRef;
-use crate::utils::{_last_null_pointer_err};
+use crate::utils::{_last_cpl_err, _last_null_pointer_err};
 use crate::vector::driver::_register_drivers;
-use crate::vector::Layer;
-use gdal_sys::{self, GDALMajorObjectH, OGRDataSourceH, OGRwkbGeometryType};
+use crate::vector::{Driver, Layer, GDAL_OF_READONLY, GDAL_OF_VECTOR, GDAL_OF_VERBOSE_ERROR};
+use gdal_sys::{self, CPLErr, GDALMajorObjectH, OGRDataSourceH, OGRErr, OGRwkbGeometryType};
 use libc::c_int;
 use std::ffi::CString;
 use std::ptr::{null, null_mut};
 
+use crate::errors::*;
 use anyhow::Result;
 
 /// Vector dataset
@@ -72,6 +73,103 @@ impl Dataset {
         })
     }
 
+    /// Open the dataset read only, only probing the given drivers (e.g. `&["GPKG"]`) instead of every
+    /// registered one.  Noticeably faster when opening many small files of a known format
+    pub fn open_with_allowed_drivers<T>(dataset: T, allowed_drivers: &[&str]) -> Result<Dataset>
+        where T: AsRef<str>
+    {
+        _register_drivers();
+        let c_dataset_str = CString::new(dataset.as_ref())?;
+
+        let driver_strings = allowed_drivers.iter().map(|d| CString::new(*d)).collect::<std::result::Result<Vec<_>, _>>()?;
+        let mut driver_ptrs: Vec<*const libc::c_char> = driver_strings.iter().map(|cs| cs.as_ptr()).collect();
+        driver_ptrs.push(null());
+
+        let c_dataset = unsafe {
+            gdal_sys::GDALOpenEx(
+                c_dataset_str.as_ptr(),
+                GDAL_OF_VECTOR | GDAL_OF_READONLY | GDAL_OF_VERBOSE_ERROR,
+                driver_ptrs.as_ptr(),
+                null(),
+                null(),
+            )
+        };
+
+        if c_dataset.is_null() {
+            Err(_last_null_pointer_err("GDALOpenEx"))?;
+        };
+        Ok(Dataset {
+            c_dataset,
+        })
+    }
+
+    /// Open a (possibly multi-GB) GeoJSON or newline delimited GeoJSON sequence read only for one
+    /// forward pass over `features()`.  GDAL reads GeoJSON files through its streaming parser and
+    /// only the GeoJSON drivers are probed.  Random access (`get_feature_by_id`) rescans the file.
+    ///
+    /// A single feature larger than `OGR_GEOJSON_MAX_OBJ_SIZE` (200 MB by default) aborts the
+    /// read.  GDAL reads that option again every time the features are read from the start, so it
+    /// can't be lifted for the open only: for huge multipolygons set it to "0" with
+    /// `config::set_config_option` for as long as the dataset is read
+    pub fn open_streaming<T>(dataset: T) -> Result<Dataset>
+        where T: AsRef<str>
+    {
+        Dataset::open_with_allowed_drivers(dataset, &[Driver::DRIVER_NAME_GEOJSON, "GeoJSONSeq"])
+    }
+
+    /// In-process ogr2ogr from this dataset to a new dataset at `dst_path`, which is returned.
+    /// `options` are the ogr2ogr command line arguments, i.e.
+    /// `["-f", "GPKG", "-t_srs", "EPSG:3035", "-clipdst", "xmin", "ymin", "xmax", "ymax"]`.
+    /// Everything is flushed to `dst_path` once the returned dataset is dropped or closed
+    pub fn vector_translate<T>(&self, dst_path: &str, options: &[T]) -> Result<Dataset>
+        where T: AsRef<str>
+    {
+        let c_dst_path = CString::new(dst_path)?;
+        let c_options = options
+            .iter()
+            .map(|o| CString::new(o.as_ref()))
+            .collect::<std::result::Result<Vec<CString>, _>>()?;
+        let mut c_option_ptrs: Vec<*mut libc::c_char> = c_options
+            .iter()
+            .map(|o| o.as_ptr() as *mut libc::c_char)
+            .collect();
+        c_option_ptrs.push(null_mut());
+
+        let translate_options = unsafe {
+            gdal_sys::GDALVectorTranslateOptionsNew(c_option_ptrs.as_mut_ptr(), null_mut())
+        };
+        if translate_options.is_null() {
+            Err(_last_null_pointer_err("GDALVectorTranslateOptionsNew"))?;
+        }
+
+        let mut src_datasets = [self.c_dataset];
+        let mut usage_error: c_int = 0;
+        let c_dataset = unsafe {
+            let c_dataset = gdal_sys::GDALVectorTranslate(
+                c_dst_path.as_ptr(),
+                null_mut(),
+                1,
+                src_datasets.as_mut_ptr(),
+                translate_options,
+                &mut usage_error,
+            );
+            gdal_sys::GDALVectorTranslateOptionsFree(translate_options);
+            c_dataset
+        };
+        if c_dataset.is_null() {
+            Err(_last_null_pointer_err("GDALVectorTranslate"))?;
+        }
+
+        Ok(Dataset { c_dataset })
+    }
+
+    /// Create an empty dataset with the Memory driver, for scratch layers
+    /// that never need to touch the disk
+    pub fn in_memory() -> Result<Dataset> {
+        let driver = Driver::get(Driver::DRIVER_NAME_MEMORY)?;
+        driver.create("")
+    }
+
     /// Get number of layers.
     pub fn count(&self) -> isize {
         (unsafe { gdal_sys::OGR_DS_GetLayerCount(self.c_dataset) }) as isize
@@ -202,6 +300,67 @@ impl Dataset {
             }
         )
     }
+
+    /// Create a new layer with the geometry type and attribute fields of `template`.
+    /// Uses the spatial ref. of `template` when `srs` is None
+    pub fn create_layer_like(&self, name: &str, template: &Layer, srs: Option<&SpatialRef>) -> Result<Layer> {
+        let template_srs = match srs {
+            Some(_) => None,
+            None => template.spatial_reference().ok(),
+        };
+        let c_srs = srs.or(template_srs.as_ref()).map_or(null_mut(), |srs| srs.c_spatial_ref);
+
+        let template_defn = template.layer_definition();
+        let c_name = CString::new(name)?;
+        let c_layer = unsafe {
+            gdal_sys::OGR_DS_CreateLayer(
+                self.c_dataset,
+                c_name.as_ptr(),
+                c_srs,
+                template_defn.get_geometry_type(),
+                null_mut(),
+            )
+        };
+        if c_layer.is_null() {
+            Err(_last_null_pointer_err("OGR_DS_CreateLayer"))?;
+        };
+
+        let mut layer = Layer {
+            c_layer,
+            _dataset: self,
+            owned: false
+        };
+        for field in template_defn.fields() {
+            layer.create_field(&field, false)?;
+        }
+
+        Ok(layer)
+    }
+
+    /// Flushes pending writes and closes the dataset, reporting any error that happened doing so.
+    /// Dropping the dataset does the same but has to ignore errors, use this when the output
+    /// must be known to be complete.  All layers of the dataset have to be dropped first
+    pub fn close(self) -> Result<()> {
+        let c_dataset = self.c_dataset;
+        std::mem::forget(self);
+
+        unsafe { gdal_sys::CPLErrorReset() };
+        let rv = unsafe { gdal_sys::OGR_DS_SyncToDisk(c_dataset) };
+        unsafe { gdal_sys::OGR_DS_Destroy(c_dataset) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OGR_DS_SyncToDisk",
+            })?;
+        }
+
+        // errors while writing the last data on close are only reported through CPLError
+        let err_class = unsafe { gdal_sys::CPLGetLastErrorType() };
+        if err_class == CPLErr::CE_Failure || err_class == CPLErr::CE_Fatal {
+            Err(_last_cpl_err(err_class))?;
+        }
+        Ok(())
+    }
 }
 
 impl Drop for Dataset {
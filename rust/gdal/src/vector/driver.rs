@@ -15,9 +15,9 @@ GNU General Public License for more details.
 You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
-use crate::utils::_last_null_pointer_err;
+use crate::utils::{_last_cpl_err, _last_null_pointer_err, _string};
 use crate::vector::{Dataset, GDAL_OF_VECTOR, GDAL_OF_READONLY, GDAL_OF_UPDATE, GDAL_OF_VERBOSE_ERROR};
-use gdal_sys::{self, OGRSFDriverH};
+use gdal_sys::{self, CPLErr, GDALDriverH, OGRSFDriverH};
 use std::ffi::CString;
 use std::ptr::null_mut;
 use std::sync::Once;
@@ -48,6 +48,9 @@ impl Driver {
     pub const DRIVER_NAME_POSTGRESQL : &'static str = "PostgreSQL";
     pub const DRIVER_NAME_MEMORY : &'static str = "Memory";
 
+    pub const CAP_CREATE_DATASOURCE : &'static str = "CreateDataSource";
+    pub const CAP_DELETE_DATASOURCE : &'static str = "DeleteDataSource";
+
     pub fn get(name: &str) -> Result<Driver> {
         _register_drivers();
         let c_name = CString::new(name)?;
@@ -59,6 +62,40 @@ impl Driver {
         }
     }
 
+    /// All registered vector drivers, e.g. to list the supported formats
+    pub fn all() -> impl Iterator<Item = Driver> {
+        _register_drivers();
+        let count = unsafe { gdal_sys::OGRGetDriverCount() };
+        (0..count).filter_map(|i| {
+            let c_driver = unsafe { gdal_sys::OGRGetDriver(i) };
+            if c_driver.is_null() {
+                None
+            } else {
+                Some(Driver { c_driver })
+            }
+        })
+    }
+
+    /// Short name of the driver, the one `Driver::get` takes, i.e. "GPKG"
+    pub fn short_name(&self) -> String {
+        let rv = unsafe { gdal_sys::OGR_Dr_GetName(self.c_driver) };
+        _string(rv)
+    }
+
+    /// Test if the driver supports a capability, i.e. `Driver::CAP_CREATE_DATASOURCE`
+    pub fn test_capability(&self, capability: &str) -> bool {
+        let c_capability = match CString::new(capability) {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+        unsafe { gdal_sys::OGR_Dr_TestCapability(self.c_driver, c_capability.as_ptr()) == 1 }
+    }
+
+    /// True if the driver can write new datasets, read only drivers return false
+    pub fn can_create(&self) -> bool {
+        self.test_capability(Driver::CAP_CREATE_DATASOURCE)
+    }
+
     pub fn create<T>(&self, path: T) -> Result<Dataset>
     where T: AsRef<str>
     {
@@ -73,6 +110,21 @@ impl Driver {
         }
     }
 
+    /// Delete a datasource with all of its files, for a shapefile this includes the
+    /// .shx, .dbf and .prj sidecar files
+    pub fn delete<T>(&self, path: T) -> Result<()>
+    where T: AsRef<str>
+    {
+        let c_filename = CString::new(path.as_ref())?;
+        let rv = unsafe {
+            gdal_sys::GDALDeleteDataset(self.c_driver as GDALDriverH, c_filename.as_ptr())
+        };
+        if rv != CPLErr::CE_None {
+            Err(_last_cpl_err(rv))?;
+        }
+        Ok(())
+    }
+
     pub fn open<T>(&self, path: T, read_only: bool) -> Result<Dataset>
     where T: AsRef<str>
     {
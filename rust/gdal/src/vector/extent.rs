@@ -0,0 +1,81 @@
+/*
+This file is part of the Building Aggregration Tool
+Copyright (C) 2022 Novel-T
+
+The Building Aggregration Tool is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+use crate::vector::Geometry;
+use gdal_sys::OGREnvelope;
+use anyhow::Result;
+
+/// Axis aligned bounding box of a layer, in the layer's coordinate system
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Extent {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Extent {
+    pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Extent {
+        Extent { min_x, min_y, max_x, max_y }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// [x, y] of the middle of the box
+    pub fn center(&self) -> [f64; 2] {
+        [(self.min_x + self.max_x) / 2.0, (self.min_y + self.max_y) / 2.0]
+    }
+
+    /// Points on the border are inside
+    pub fn contains_point(&self, x: f64, y: f64) -> bool {
+        self.min_x <= x && x <= self.max_x && self.min_y <= y && y <= self.max_y
+    }
+
+    /// True if the boxes overlap or touch
+    pub fn intersects(&self, other: &Extent) -> bool {
+        self.min_x <= other.max_x && other.min_x <= self.max_x
+            && self.min_y <= other.max_y && other.min_y <= self.max_y
+    }
+
+    /// The box as a polygon, see `Geometry::bbox`
+    pub fn to_bbox_geometry(&self) -> Result<Geometry> {
+        Geometry::bbox(self.min_x, self.min_y, self.max_x, self.max_y)
+    }
+}
+
+impl From<OGREnvelope> for Extent {
+    fn from(env: OGREnvelope) -> Self {
+        Extent::new(env.MinX, env.MinY, env.MaxX, env.MaxY)
+    }
+}
+
+impl From<Extent> for OGREnvelope {
+    fn from(extent: Extent) -> Self {
+        OGREnvelope {
+            MinX: extent.min_x,
+            MaxX: extent.max_x,
+            MinY: extent.min_y,
+            MaxY: extent.max_y,
+        }
+    }
+}
@@ -19,7 +19,8 @@ use crate::utils::{_last_null_pointer_err, _string};
 use crate::vector::geometry::{Geometry, FeatureGeometry};
 use crate::vector::layer::Layer;
 use crate::vector::LayerDefinition;
-use gdal_sys::{self, OGRErr, OGRFeatureH, OGRFieldType};
+use crate::spatial_ref::SpatialRef;
+use gdal_sys::{self, OGRErr, OGRFeatureH, OGRFieldType, OGRwkbGeometryType};
 use libc::{c_double, c_int};
 use std::ffi::CString;
 
@@ -29,6 +30,7 @@ use serde::{Deserialize, Serialize};
 use crate::errors::*;
 use anyhow::Result;
 use std::convert::TryFrom;
+use std::collections::HashMap;
 
 /// OGR Feature
 /// This corresponds to an item returned by OGR_L_GetNextFeature
@@ -44,6 +46,12 @@ fn field_from_idx(feature: OGRFeatureH, field_id: i32) -> Result<FieldValue> {
 
     let field_defn = unsafe { gdal_sys::OGR_F_GetFieldDefnRef(feature, field_id) };
     let field_type = unsafe { gdal_sys::OGR_Fld_GetType(field_defn) };
+    read_field_as(feature, field_id, field_type)
+}
+
+/// Reads the field with the OGR_F_GetFieldAs* getter of `field_type`, GDAL converting the value
+/// if the field is declared with another type
+fn read_field_as(feature: OGRFeatureH, field_id: i32, field_type: OGRFieldType::Type) -> Result<FieldValue> {
     match field_type {
         OGRFieldType::OFTString => {
             let rv = unsafe { gdal_sys::OGR_F_GetFieldAsString(feature, field_id) };
@@ -115,7 +123,7 @@ fn get_field_datetime(feature: OGRFeatureH, field_id: c_int) -> Result<DateTime<
     };
     if success == 0 {
         Err(ErrorKind::OgrError {
-            err: OGRErr::OGRERR_FAILURE,
+            err: OgrError::Failure,
             method_name: "OGR_F_GetFieldAsDateTime",
         })?;
     }
@@ -232,6 +240,13 @@ impl<'l, 'd> Feature<'l, 'd> {
         let rv = unsafe { gdal_sys::OGR_F_GetFieldAsString(self.c_feature, field_id) };
         _string(rv)
     }
+    /// Field formatted as text by GDAL, whatever its type; `None` if unset or null
+    pub fn field_as_string(&self, field_id: i32) -> Option<String> {
+        if !self.is_field_set_and_not_null(field_id) {
+            return None;
+        }
+        Some(self.get_field_as_string(field_id))
+    }
     pub fn get_field_as_int(&self, field_id: i32) -> i32 {
         let rv = unsafe { gdal_sys::OGR_F_GetFieldAsInteger(self.c_feature, field_id) };
         rv
@@ -245,6 +260,87 @@ impl<'l, 'd> Feature<'l, 'd> {
         rv
     }
 
+    /// Value of a numeric (integer, integer64 or real) field as f64.
+    /// Errors, naming the field, if it is missing, unset/null or not numeric
+    pub fn field_as_f64(&self, name: &str) -> Result<f64> {
+        let field_id = self.numeric_field_index(name, "field_as_f64", &[
+            OGRFieldType::OFTInteger, OGRFieldType::OFTInteger64, OGRFieldType::OFTReal
+        ])?;
+        Ok(self.get_field_as_real(field_id))
+    }
+
+    /// Value of an integer or integer64 field as i64, real fields are refused rather than truncated.
+    /// Errors, naming the field, if it is missing, unset/null or not an integer
+    pub fn field_as_i64(&self, name: &str) -> Result<i64> {
+        let field_id = self.numeric_field_index(name, "field_as_i64", &[
+            OGRFieldType::OFTInteger, OGRFieldType::OFTInteger64
+        ])?;
+        Ok(self.get_field_as_int64(field_id))
+    }
+
+    fn numeric_field_index(&self, name: &str, method_name: &'static str, allowed_types: &[OGRFieldType::Type]) -> Result<i32> {
+        let c_name = CString::new(name)?;
+        let field_id = unsafe { gdal_sys::OGR_F_GetFieldIndex(self.c_feature, c_name.as_ptr()) };
+        if field_id == -1 {
+            Err(ErrorKind::InvalidFieldName {
+                field_name: name.to_string(),
+                method_name,
+            })?;
+        }
+
+        let field_type = unsafe {
+            gdal_sys::OGR_Fld_GetType(gdal_sys::OGR_F_GetFieldDefnRef(self.c_feature, field_id))
+        };
+        if !allowed_types.contains(&field_type) {
+            Err(ErrorKind::NonNumericField {
+                field_name: name.to_string(),
+                field_type,
+                method_name,
+            })?;
+        }
+
+        if !self.is_field_set_and_not_null(field_id) {
+            Err(ErrorKind::NullField {
+                field_name: name.to_string(),
+                method_name,
+            })?;
+        }
+
+        Ok(field_id)
+    }
+
+    /// Copy the fid, geometry and all the field values into a ReadOnlyFeature.
+    /// Unset or null fields are stored as FieldValue::Null
+    pub fn to_read_only(&self) -> Result<ReadOnlyFeature> {
+        let field_count = self.field_count();
+        let mut fields = HashMap::with_capacity(field_count as usize);
+        for field_id in 0..field_count {
+            let name = unsafe {
+                let field_defn = gdal_sys::OGR_F_GetFieldDefnRef(self.c_feature, field_id);
+                _string(gdal_sys::OGR_Fld_GetNameRef(field_defn))
+            };
+            let value = if self.is_field_set_and_not_null(field_id) {
+                self.field_from_idx(field_id)?
+            } else {
+                FieldValue::Null
+            };
+            fields.insert(name, value);
+        }
+
+        let geometry = self.geometry().as_geom();
+        let geometry_wkb = if geometry.c_geometry.is_null() {
+            None
+        } else {
+            Some(geometry.ewkb_bytes_raw()?)
+        };
+
+        Ok(ReadOnlyFeature {
+            fid: self.fid(),
+            geometry_wkb,
+            fields,
+        })
+    }
+
     pub fn fid(&self) -> i64 {
         let fid = unsafe { gdal_sys::OGR_F_GetFID(self.c_feature) };
         fid
@@ -267,6 +363,23 @@ impl<'l, 'd> Feature<'l, 'd> {
         }
     }
 
+    /// False when the feature has no geometry, `geometry()` must then not be used
+    pub fn has_geometry(&self) -> bool {
+        !unsafe { gdal_sys::OGR_F_GetGeometryRef(self.c_feature) }.is_null()
+    }
+
+    /// The geometry as little endian WKB, exported straight from the feature without cloning it.
+    /// GEOS reads it with `WKBReader::read_wkb`, the fast way to get features into geo_util
+    pub fn geometry_wkb(&self) -> Result<Vec<u8>> {
+        let geometry = self.geometry();
+        if geometry.c_geometry_ref.is_null() {
+            Err(ErrorKind::UnlinkedGeometry {
+                method_name: "geometry_wkb",
+            })?;
+        }
+        geometry.as_geom().ewkb_bytes_raw()
+    }
+
     pub fn geometry_by_name<'f>(&'f self, field_name: &str) ->  Result<FeatureGeometry<'f, 'l, 'd>> {
         let c_str_field_name = CString::new(field_name)?;
         let idx =
@@ -295,6 +408,37 @@ impl<'l, 'd> Feature<'l, 'd> {
     }
 
 
+    pub fn geom_field_count(&self) -> i32 {
+        unsafe { gdal_sys::OGR_F_GetGeomFieldCount(self.c_feature) }
+    }
+
+    /// Geometry of the geometry field `idx` with the type and spatial ref. the field declares,
+    /// for layers with several geometry columns possibly in different CRS
+    pub fn geometry_field<'f>(&'f self, idx: i32)
+        -> Result<(FeatureGeometry<'f, 'l, 'd>, OGRwkbGeometryType::Type, Option<SpatialRef>)>
+    {
+        if idx < 0 || idx >= self.geom_field_count() {
+            Err(ErrorKind::InvalidFieldIndex {
+                index: idx as usize,
+                method_name: "geometry_field",
+            })?;
+        }
+
+        let c_field_defn = unsafe { gdal_sys::OGR_F_GetGeomFieldDefnRef(self.c_feature, idx) };
+        if c_field_defn.is_null() {
+            Err(_last_null_pointer_err("OGR_F_GetGeomFieldDefnRef"))?;
+        }
+        let geometry_type = unsafe { gdal_sys::OGR_GFld_GetType(c_field_defn) };
+        let c_spatial_ref = unsafe { gdal_sys::OGR_GFld_GetSpatialRef(c_field_defn) };
+        let spatial_ref = if c_spatial_ref.is_null() {
+            None
+        } else {
+            Some(SpatialRef::from_c_obj(c_spatial_ref)?)
+        };
+
+        Ok((self.geometry_by_index(idx)?, geometry_type, spatial_ref))
+    }
+
     pub fn set_field_string(&self, field_name: &str, value: &str) -> Result<()> {
         let c_str_field_name = CString::new(field_name)?;
         let c_str_value = CString::new(value)?;
@@ -443,19 +587,21 @@ impl<'l, 'd> Feature<'l, 'd> {
         let rv = unsafe { gdal_sys::OGR_F_SetGeometryDirectly(self.c_feature, geom.c_geometry) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_SetGeometry",
             })?;
         }
         Ok(())
     }
 
-    pub fn set_geometry(&mut self, geom: Geometry) -> Result<()> {
+    /// Replace the geometry of the feature with a copy of `geom`, use `Layer::set_feature`
+    /// to write the change back
+    pub fn set_geometry(&mut self, geom: &Geometry) -> Result<()> {
         //sets in memory, makes a copy of geom
         let rv = unsafe { gdal_sys::OGR_F_SetGeometry(self.c_feature, geom.c_geometry) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_SetGeometry",
             })?;
         }
@@ -469,7 +615,7 @@ impl<'l, 'd> Feature<'l, 'd> {
         let rv = unsafe { gdal_sys::OGR_F_SetGeomFieldDirectly(self.c_feature, index,geom.c_geometry) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_F_SetGeomFieldDirectly",
             })?;
         }
@@ -481,7 +627,7 @@ impl<'l, 'd> Feature<'l, 'd> {
         let rv = unsafe { gdal_sys::OGR_F_SetGeomField(self.c_feature, index, geom.c_geometry) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_F_SetGeomField",
             })?;
         }
@@ -553,7 +699,7 @@ impl<'l, 'd> Feature<'l, 'd> {
         let rv = unsafe { gdal_sys::OGR_F_SetFID(self.c_feature, fid)};
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_F_SetFID",
             })?;
         }
@@ -564,7 +710,7 @@ impl<'l, 'd> Feature<'l, 'd> {
         let rv = unsafe { gdal_sys::OGR_L_CreateFeature(lyr.c_layer(), self.c_feature) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_L_CreateFeature",
             })?;
         }
@@ -580,6 +726,33 @@ impl<'l, 'd> Drop for Feature<'l, 'd> {
     }
 }
 
+/// Owned copy of a feature, detached from its layer so it can be sent to another thread.
+///
+/// GDAL datasets, layers and features must not be shared between threads, for parallel
+/// processing either give each worker its own Dataset (see Layer::feature_ranges) or read
+/// on one thread and send ReadOnlyFeatures to the workers.
+#[derive(Debug)]
+pub struct ReadOnlyFeature {
+    pub fid: i64,
+    /// ISO WKB of the first geometry field, None if the feature has no geometry
+    pub geometry_wkb: Option<Vec<u8>>,
+    pub fields: HashMap<String, FieldValue>,
+}
+
+impl ReadOnlyFeature {
+    /// Rebuild the OGR geometry from the stored WKB
+    pub fn geometry(&self) -> Result<Option<Geometry>> {
+        match &self.geometry_wkb {
+            Some(wkb) => Ok(Some(Geometry::from_wkb(wkb)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldValue> {
+        self.fields.get(name)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum FieldValue {
     IntegerValue(i32),
@@ -594,6 +767,22 @@ pub enum FieldValue {
 }
 
 impl FieldValue {
+    /// Read field `field_idx` of `feature` as the `FieldValue` variant of `field_type`, normally
+    /// the declared type of the field (see `Field::field_type`).  Unset or null fields give
+    /// `FieldValue::Null`
+    pub fn from_ogr_field(feature: &Feature, field_idx: i32, field_type: OGRFieldType::Type) -> Result<FieldValue> {
+        if field_idx < 0 || field_idx >= feature.field_count() {
+            Err(ErrorKind::InvalidFieldIndex {
+                index: field_idx as usize,
+                method_name: "from_ogr_field",
+            })?;
+        }
+        if !feature.is_field_set_and_not_null(field_idx) {
+            return Ok(FieldValue::Null);
+        }
+        read_field_as(feature.c_feature, field_idx, field_type)
+    }
+
     /// Interpret the value as `String`. Panics if the value is something else.
     pub fn into_string(self) -> Option<String> {
         match self {
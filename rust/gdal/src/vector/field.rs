@@ -32,7 +32,7 @@ use crate::spatial_ref::SpatialRef;
 
 /// Owned, even after using add_to_layer
 pub struct FieldDefinition {
-    c_obj: OGRFieldDefnH,
+    pub(crate) c_obj: OGRFieldDefnH,
 }
 
 impl Drop for FieldDefinition {
@@ -66,12 +66,23 @@ impl FieldDefinition {
         //OGR_AreTypeSubTypeCompatible ?
         unsafe { gdal_sys::OGR_Fld_SetSubType(self.c_obj, sub_type ) };
     }
+    /// Default value as an SQL literal: quoted string (`'n/a'`), number, or one of
+    /// `CURRENT_TIMESTAMP`, `CURRENT_DATE`, `CURRENT_TIME`.  Only honored by some drivers (GPKG, PostgreSQL)
+    pub fn set_default(&self, default: &str) -> Result<()> {
+        let c_str = CString::new(default)?;
+        unsafe { gdal_sys::OGR_Fld_SetDefault(self.c_obj, c_str.as_ptr()) };
+        Ok(())
+    }
+    /// false adds a NOT NULL constraint, fields are nullable by default
+    pub fn set_nullable(&self, nullable: bool) {
+        unsafe { gdal_sys::OGR_Fld_SetNullable(self.c_obj, nullable as c_int) };
+    }
     pub fn add_to_layer(&self, layer: &mut Layer) -> Result<()> {
         //seems like you still need to destroy it afterwards, so self still runs drop afterwards
         let rv = unsafe { gdal_sys::OGR_L_CreateField(layer.c_layer(), self.c_obj, 1) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_L_CreateFeature",
             })?;
         }
@@ -108,6 +119,20 @@ impl<'f: 'l, 'l: 'd, 'd> Field<'f, 'l, 'd> {
     pub fn precision(&'f self) -> i32 {
         unsafe { gdal_sys::OGR_Fld_GetPrecision(self.c_field_defn) }
     }
+
+    /// Default value as set by `FieldDefinition::set_default`, None if there is none
+    pub fn default(&'f self) -> Option<String> {
+        let rv = unsafe { gdal_sys::OGR_Fld_GetDefault(self.c_field_defn) };
+        if rv.is_null() {
+            None
+        } else {
+            Some(_string(rv))
+        }
+    }
+
+    pub fn is_nullable(&'f self) -> bool {
+        unsafe { gdal_sys::OGR_Fld_IsNullable(self.c_field_defn) != 0 }
+    }
 }
 
 //feature 'f less that layer 'l less than dataset 'd
@@ -212,7 +237,7 @@ impl GeomField {
         let rv = unsafe { gdal_sys::OGR_L_CreateGeomField(layer.c_layer(), self.c_field_defn, 1) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_L_CreateGeomField",
             })?;
         }
@@ -254,6 +279,20 @@ pub fn geometry_type_to_name(geom_type: OGRwkbGeometryType::Type) -> Result<&'st
 }
 
 
+/// Rust type `FieldValue::from_ogr_field` reads a field of this type into, i.e. "i64" for
+/// OFTInteger64.  "unsupported" for the types without a `FieldValue` variant
+pub fn rust_type_name(field_type: OGRFieldType::Type) -> &'static str {
+    match field_type {
+        OGRFieldType::OFTInteger => "i32",
+        OGRFieldType::OFTInteger64 => "i64",
+        OGRFieldType::OFTReal => "f64",
+        OGRFieldType::OFTString => "String",
+        OGRFieldType::OFTRealList => "Vec<f64>",
+        OGRFieldType::OFTDate | OGRFieldType::OFTDateTime => "DateTime<FixedOffset>",
+        _ => "unsupported",
+    }
+}
+
 pub fn field_type_to_name(field_type: OGRFieldType::Type) -> Result<&'static str> {
     unsafe {
         let name = gdal_sys::OGR_GetFieldTypeName(field_type);
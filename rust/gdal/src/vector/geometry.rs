@@ -23,14 +23,24 @@ use std::ffi::{CString};
 use std::ptr::null_mut;
 
 use crate::errors::*;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use crate::vector::{Feature};
-use std::{ptr, slice};
+use std::{fmt, ptr, slice};
+use geo_types::Coordinate;
 
 
 
 
 
+/// Algorithm used by Geometry::make_valid_ext
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MakeValidMethod {
+    /// Original GEOS algorithm, keeps all the input vertices including collapsed rings as lines
+    Linework,
+    /// Rebuilds the polygons from their rings, collapsed parts are dropped (GDAL >= 3.4)
+    Structure,
+}
+
 /// OGR Geometry
 pub struct Geometry {
     //move semantics so having a pointer is fine, cannot copy / clone
@@ -72,14 +82,17 @@ impl Geometry {
 
     /// Create a geometry by parsing a
     /// [WKT](https://en.wikipedia.org/wiki/Well-known_text) string.
+    ///
+    /// Accepts the ISO dimension tags, i.e. `POINT Z (1 2 3)`, `LINESTRING M (...)` or `POLYGON ZM (...)`
     pub fn from_wkt(wkt: &str) -> Result<Geometry> {
         let c_wkt = CString::new(wkt)?;
-        let mut c_wkt_ptr = c_wkt.into_raw();
+        // OGR advances this pointer while parsing, c_wkt keeps ownership of the string
+        let mut c_wkt_ptr = c_wkt.as_ptr() as *mut libc::c_char;
         let mut c_geom = null_mut();
         let rv = unsafe { gdal_sys::OGR_G_CreateFromWkt(&mut c_wkt_ptr, null_mut(), &mut c_geom) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_CreateFromWkt",
             })?;
         }
@@ -87,6 +100,21 @@ impl Geometry {
         Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
     }
 
+    /// Create a geometry from ISO WKB bytes, i.e. the output of `ewkb_bytes_raw`
+    pub fn from_wkb(wkb: &[u8]) -> Result<Geometry> {
+        let mut c_geom = null_mut();
+        let rv = unsafe {
+            gdal_sys::OGR_G_CreateFromWkb(wkb.as_ptr() as *const c_void, null_mut(), &mut c_geom, wkb.len() as c_int)
+        };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OGR_G_CreateFromWkb",
+            })?;
+        }
+        Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
+    }
+
     pub fn from_x_y(x: f64, y:f64) -> Result<Geometry> {
         //let mut c_geom = null_mut();
 
@@ -98,7 +126,7 @@ impl Geometry {
         //let c_geom: *mut c_void = &mut point as *mut _ as *mut c_void;
         /*if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGRPoint_OGRPoint2",
             })?;
         }*/
@@ -143,7 +171,7 @@ impl Geometry {
         let rv = unsafe { gdal_sys::OGR_G_ExportToWkt(self.c_geometry, &mut c_wkt) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_ExportToWkt",
             })?;
         }
@@ -157,6 +185,12 @@ impl Geometry {
         _string(rv)
     }
 
+    /// Textual geometry type, i.e. "POLYGON" or "MULTILINESTRING", for error messages.
+    /// For the name of a layer's expected type see `geometry_type_to_name`
+    pub fn type_name(&self) -> String {
+        self.geometry_name()
+    }
+
     //pub unsafe fn c_geometry(&self) -> OGRGeometryH {
      //   self.c_geometry
    // }
@@ -191,10 +225,65 @@ impl Geometry {
         (0..length).map(|i| self.get_point(i)).collect()
     }
 
+    /// Every coordinate (x, y, z) of any geometry type, walking the rings of polygons and the
+    /// parts of multi geometries / collections in order.  z is 0 for 2D geometries
+    pub fn all_coords(&self) -> Vec<[f64; 3]> {
+        let mut coords = Vec::with_capacity(self.point_count());
+        self.collect_coords(&mut coords);
+        coords
+    }
+
+    fn collect_coords(&self, coords: &mut Vec<[f64; 3]>) {
+        let sub_count = self.geometry_count();
+        if sub_count > 0 {
+            for n in 0..sub_count {
+                self.get_geometry(n).collect_coords(coords);
+            }
+            return;
+        }
+        let length = unsafe { gdal_sys::OGR_G_GetPointCount(self.c_geometry) };
+        coords.extend((0..length).map(|i| {
+            let (x, y, z) = self.get_point_xyz(i);
+            [x, y, z]
+        }));
+    }
+
+    /// Same points as `get_point_vec` as (x, y) tuples, the shape geo_types::Coordinate converts from
+    pub fn get_point_vec_2d(&self) -> Vec<(f64, f64)> {
+        self.get_point_vec().into_iter().map(|[x, y]| (x, y)).collect()
+    }
+
+    /// Z value of every point of a point / line geometry, 0 for 2D geometries
+    pub fn get_z_values(&self) -> Vec<f64> {
+        let length = unsafe { gdal_sys::OGR_G_GetPointCount(self.c_geometry) };
+        (0..length)
+            .map(|i| unsafe { gdal_sys::OGR_G_GetZ(self.c_geometry, i) })
+            .collect()
+    }
+
     pub fn get_point_iterator(&self) -> PointIterator {
         PointIterator::new(self.c_geometry)
     }
 
+    /// Coordinates of the exterior ring of a polygon as geo_types coordinates, empty for an
+    /// empty polygon.  Fails for every other geometry type, multipolygons included: iterate
+    /// their parts with `get_geometry`
+    pub fn exterior_coords(&self) -> Result<Vec<Coordinate<f64>>> {
+        let flat_type = unsafe { gdal_sys::OGR_GT_Flatten(self.geometry_type()) };
+        if flat_type != OGRwkbGeometryType::wkbPolygon {
+            Err(ErrorKind::OgrError {
+                err: OgrError::UnsupportedGeometryType,
+                method_name: "exterior_coords",
+            })?;
+        }
+        if self.geometry_count() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let ring = self.get_geometry(0);
+        Ok(ring.get_point_iterator().map(|[x, y]| Coordinate { x, y }).collect())
+    }
+
     /// Compute the convex hull of this geometry.
     pub fn convex_hull(&self) -> Result<Geometry> {
         let c_geom = unsafe { gdal_sys::OGR_G_ConvexHull(self.c_geometry) };
@@ -209,7 +298,7 @@ impl Geometry {
         let rv = unsafe { gdal_sys::OGR_G_Centroid(self.c_geometry, c_point.c_geometry) } as u32;
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_Centroid",
             })?;
         }
@@ -231,6 +320,18 @@ impl Geometry {
         e
     }
 
+    /// Cheap broad phase test, true if the envelopes (bounding boxes) overlap or touch.
+    /// Empty geometries never intersect
+    pub fn envelopes_intersect(&self, other_geom: &Self) -> bool {
+        if self.is_empty() || other_geom.is_empty() {
+            return false;
+        }
+        let e = self.envelope();
+        let o = other_geom.envelope();
+
+        e.MinX <= o.MaxX && o.MinX <= e.MaxX && e.MinY <= o.MaxY && o.MinY <= e.MaxY
+    }
+
     pub fn intersects(&self, other_geom: &Self) -> bool {
         unsafe {
             let r = gdal_sys::OGR_G_Intersects(self.c_geometry, other_geom.c_geometry);
@@ -238,10 +339,62 @@ impl Geometry {
         }
     }
 
+    /// Topological equality (OGR_G_Equals), the vertices may differ in order or
+    /// starting point as long as both geometries cover the same points.
+    /// Use `equals_with_tolerance` to compare coordinates directly
+    pub fn equals(&self, other_geom: &Self) -> bool {
+        unsafe { gdal_sys::OGR_G_Equals(self.c_geometry, other_geom.c_geometry) == 1 }
+    }
+
+    /// Structural equality, same geometry types and number of parts, and every vertex
+    /// within `tolerance` of the matching vertex in `other_geom`.
+    /// Handy to compare reprojected geometries without relying on WKT formatting
+    pub fn equals_with_tolerance(&self, other_geom: &Self, tolerance: f64) -> bool {
+        if self.geometry_type() != other_geom.geometry_type() {
+            return false;
+        }
+
+        let geometry_count = self.geometry_count();
+        if geometry_count != other_geom.geometry_count() {
+            return false;
+        }
+
+        if geometry_count > 0 {
+            return (0..geometry_count).all(|n| {
+                self.get_geometry(n)
+                    .equals_with_tolerance(&other_geom.get_geometry(n), tolerance)
+            });
+        }
+
+        let points = self.get_point_vec();
+        let other_points = other_geom.get_point_vec();
+
+        points.len() == other_points.len()
+            && points.iter().zip(other_points.iter()).all(|(p, o)| {
+                (p[0] - o[0]).abs() <= tolerance && (p[1] - o[1]).abs() <= tolerance
+            })
+    }
+
     pub fn geometry_type(&self) -> OGRwkbGeometryType::Type {
         unsafe { gdal_sys::OGR_G_GetGeometryType(self.c_geometry) }
     }
 
+    /// 2 for XY geometries, 3 if the geometry has a Z.  M values are not counted
+    pub fn coordinate_dimension(&self) -> i32 {
+        unsafe { gdal_sys::OGR_G_CoordinateDimension(self.c_geometry) }
+    }
+
+    /// True if the geometry has M values
+    pub fn is_measured(&self) -> bool {
+        unsafe { gdal_sys::OGR_G_IsMeasured(self.c_geometry) != 0 }
+    }
+
+    /// Force the geometry to 2 or 3 dimensions; going to 2 drops the Z values,
+    /// going to 3 sets Z to 0
+    pub fn set_coordinate_dimension(&mut self, dimension: i32) {
+        unsafe { gdal_sys::OGR_G_SetCoordinateDimension(self.c_geometry, dimension) };
+    }
+
     pub fn geometry_count(&self) -> usize {
         let cnt = unsafe { gdal_sys::OGR_G_GetGeometryCount(self.c_geometry) };
         cnt as usize
@@ -266,12 +419,33 @@ impl Geometry {
         rv
     }
 
-    pub fn get_linear_geometry(&self) -> Geometry {
-        let rv = unsafe {
-            let c_geom = gdal_sys::OGR_G_GetLinearGeometry(self.c_geometry, 0.0, null_mut());
-            Geometry::with_c_geometry(c_geom, true)
-        };
-        rv
+    /// The parts of a multi geometry or collection, or the rings of a polygon, in order.
+    /// Nested collections are yielded as is, not recursed into; empty for points and lines.
+    /// Like `get_geometry` the parts are not owned, don't keep them longer than self
+    pub fn sub_geometries(&self) -> impl Iterator<Item = Geometry> + '_ {
+        (0..self.geometry_count()).map(move |n| self.get_geometry(n))
+    }
+
+    /// Approximate curves (CircularString, CurvePolygon, ...) with line segments.
+    /// `max_angle_step` is the largest arc angle in degrees covered by one segment, 0 for the GDAL default (4 degrees)
+    pub fn get_linear_geometry(&self, max_angle_step: f64) -> Result<Geometry> {
+        let c_geom = unsafe { gdal_sys::OGR_G_GetLinearGeometry(self.c_geometry, max_angle_step, null_mut()) };
+        if c_geom.is_null() {
+            Err(_last_null_pointer_err("OGR_G_GetLinearGeometry"))?;
+        }
+        Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
+    }
+
+    /// Inverse of `get_linear_geometry`: try to recognize arcs in line segments and return a
+    /// curve geometry (CircularString, CompoundCurve, CurvePolygon, ...).  This is a best-effort
+    /// arc fitting, it works reliably on geometries linearized by GDAL itself (which hides the arc
+    /// parameters in the low bits of the coordinates), other inputs are usually returned as lines
+    pub fn get_curve_geometry(&self) -> Result<Geometry> {
+        let c_geom = unsafe { gdal_sys::OGR_G_GetCurveGeometry(self.c_geometry, null_mut()) };
+        if c_geom.is_null() {
+            Err(_last_null_pointer_err("OGR_G_GetCurveGeometry"))?;
+        }
+        Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
     }
 
     pub fn make_valid(&self) -> Geometry {
@@ -282,6 +456,38 @@ impl Geometry {
         rv
     }
 
+    /// Make valid with a choice of algorithm, see MakeValidMethod.
+    ///
+    /// Structure needs GDAL 3.4 (the `gdal_3_4` feature), without it an error is
+    /// returned and callers should fall back to `MakeValidMethod::Linework`
+    pub fn make_valid_ext(&self, method: MakeValidMethod) -> Result<Geometry> {
+        match method {
+            MakeValidMethod::Linework => {
+                let c_geom = unsafe { gdal_sys::OGR_G_MakeValid(self.c_geometry) };
+                if c_geom.is_null() {
+                    Err(_last_null_pointer_err("OGR_G_MakeValid"))?;
+                }
+                Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
+            }
+            #[cfg(feature = "gdal_3_4")]
+            MakeValidMethod::Structure => {
+                let c_method = CString::new("METHOD=STRUCTURE")?;
+                let mut c_options = vec![c_method.as_ptr() as *mut libc::c_char, null_mut()];
+                let c_geom = unsafe {
+                    gdal_sys::OGR_G_MakeValidEx(self.c_geometry, c_options.as_mut_ptr())
+                };
+                if c_geom.is_null() {
+                    Err(_last_null_pointer_err("OGR_G_MakeValidEx"))?;
+                }
+                Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
+            }
+            #[cfg(not(feature = "gdal_3_4"))]
+            MakeValidMethod::Structure => {
+                anyhow::bail!("MakeValidMethod::Structure requires GDAL 3.4 (gdal_3_4 feature), use MakeValidMethod::Linework")
+            }
+        }
+    }
+
     pub fn to_multi_polygon(&mut self) -> Geometry {
         //Ownership is surrendered then passed back, so we must be owned
         assert!(self.owned);
@@ -316,6 +522,103 @@ impl Geometry {
         rv
     }
 
+    /// Keeps only the polygonal parts of the geometry as a MultiPolygon, i.e. for the
+    /// GeometryCollection mixing points, lines and polygons an overlay can return.
+    /// A geometry without any polygon gives an empty MultiPolygon.
+    pub fn polygons_only(&self) -> Result<Geometry> {
+        let mut multi_polygon = Geometry::empty(OGRwkbGeometryType::wkbMultiPolygon)?;
+        self.add_polygons_to(&mut multi_polygon)?;
+        Ok(multi_polygon)
+    }
+
+    /// Dissolves the polygonal parts of `geoms` into a single geometry with the GEOS cascaded
+    /// union (through OGR), much faster than unioning the geometries one by one.
+    /// Points and lines are ignored, no polygons at all gives an empty MultiPolygon.
+    pub fn union_all(geoms: &[Geometry]) -> Result<Geometry> {
+        let mut multi_polygon = Geometry::empty(OGRwkbGeometryType::wkbMultiPolygon)?;
+        for geom in geoms {
+            geom.add_polygons_to(&mut multi_polygon)?;
+        }
+        if multi_polygon.is_empty() {
+            return Ok(multi_polygon);
+        }
+
+        let c_geom = unsafe { gdal_sys::OGR_G_UnionCascaded(multi_polygon.c_geometry) };
+        if c_geom.is_null() {
+            Err(_last_null_pointer_err("OGR_G_UnionCascaded"))?;
+        }
+        Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
+    }
+
+    fn add_polygons_to(&self, multi_polygon: &mut Geometry) -> Result<()> {
+        match unsafe { gdal_sys::OGR_GT_Flatten(self.geometry_type()) } {
+            OGRwkbGeometryType::wkbPolygon => {
+                multi_polygon.add_geometry(self.clone())?;
+            }
+            OGRwkbGeometryType::wkbMultiPolygon | OGRwkbGeometryType::wkbGeometryCollection => {
+                for n in 0..self.geometry_count() {
+                    self.get_geometry(n).add_polygons_to(multi_polygon)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Orients rings the way Shapefiles expect: exterior rings clockwise, interior rings counter-clockwise.
+    /// Applies to polygons, multipolygons and collections of them, other geometries are left untouched.
+    /// Note GEOS normalize uses the opposite convention
+    pub fn normalize_rings(&mut self) {
+        let flat_type = unsafe { gdal_sys::OGR_GT_Flatten(self.geometry_type()) };
+        match flat_type {
+            OGRwkbGeometryType::wkbPolygon => {
+                for i in 0..self.geometry_count() {
+                    let mut ring = self.get_geometry(i);
+                    let clockwise = ring.signed_ring_area() < 0.0;
+                    // exterior ring is the first one
+                    if clockwise != (i == 0) {
+                        ring.reverse_points();
+                    }
+                }
+            }
+            OGRwkbGeometryType::wkbMultiPolygon | OGRwkbGeometryType::wkbGeometryCollection => {
+                for i in 0..self.geometry_count() {
+                    self.get_geometry(i).normalize_rings();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Shoelace formula, positive for counter-clockwise rings
+    fn signed_ring_area(&self) -> f64 {
+        let points = self.get_point_vec();
+        let twice_area: f64 = points
+            .iter()
+            .zip(points.iter().skip(1))
+            .map(|(p1, p2)| p1[0] * p2[1] - p2[0] * p1[1])
+            .sum();
+        twice_area / 2.0
+    }
+
+    fn reverse_points(&mut self) {
+        let point_count = self.point_count() as i32;
+        let points: Vec<(f64, f64, f64)> = (0..point_count).map(|i| self.get_point_xyz(i)).collect();
+        // SetPoint would promote a 2D geometry to 3D
+        let is_3d = self.coordinate_dimension() == 3;
+        for (i, (x, y, z)) in points.into_iter().rev().enumerate() {
+            unsafe {
+                if is_3d {
+                    gdal_sys::OGR_G_SetPoint(self.c_geometry, i as c_int, x, y, z);
+                } else {
+                    gdal_sys::OGR_G_SetPoint_2D(self.c_geometry, i as c_int, x, y);
+                }
+            }
+        }
+    }
+
+    /// True if the geometry has curve types, with `look_for_non_linear` only if those curves are
+    /// not made of straight segments only, i.e. a CompoundCurve of LineStrings doesn't count
     pub fn has_curve_geometry(&self, look_for_non_linear: bool) -> bool {
         let i_look_for_non_linear = look_for_non_linear.into();
         let rv = unsafe {
@@ -331,7 +634,7 @@ impl Geometry {
             unsafe { gdal_sys::OGR_G_AddGeometryDirectly(self.c_geometry, sub.c_geometry) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_AddGeometryDirectly",
             })?;
         }
@@ -345,13 +648,14 @@ impl Geometry {
     }
 
     // Transform the geometry inplace (when we own the Geometry)
+    // On error the geometry may be partially transformed, use `transform` to keep it intact
     pub fn transform_inplace(&mut self, htransform: &CoordTransform) -> Result<()> {
         assert!(self.owned);
 
         let rv = unsafe { gdal_sys::OGR_G_Transform(self.c_geometry, htransform.to_c_hct()) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_Transform",
             })?;
         }
@@ -359,23 +663,25 @@ impl Geometry {
     }
 
     // Return a new transformed geometry (when the Geometry is owned by a Feature)
+    // Either the whole geometry is transformed or an error is returned, self is never modified
     pub fn transform(&self, htransform: &CoordTransform) -> Result<Geometry> {
-        let new_c_geom = unsafe { gdal_sys::OGR_G_Clone(self.c_geometry) };
-        let rv = unsafe { gdal_sys::OGR_G_Transform(new_c_geom, htransform.to_c_hct()) };
+        // owned, so the clone is freed if the transform fails
+        let new_geom = self.clone();
+        let rv = unsafe { gdal_sys::OGR_G_Transform(new_geom.c_geometry, htransform.to_c_hct()) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_Transform",
             })?;
         }
-        Ok(unsafe { Geometry::with_c_geometry(new_c_geom, true) })
+        Ok(new_geom)
     }
 
     pub fn transform_to_inplace(&self, spatial_ref: &SpatialRef) -> Result<()> {
         let rv = unsafe { gdal_sys::OGR_G_TransformTo(self.c_geometry, spatial_ref.c_spatial_ref) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_TransformTo",
             })?;
         }
@@ -387,17 +693,62 @@ impl Geometry {
         let rv = unsafe { gdal_sys::OGR_G_TransformTo(new_c_geom, spatial_ref.c_spatial_ref) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_G_TransformTo",
             })?;
         }
         Ok(unsafe { Geometry::with_c_geometry(new_c_geom, true) })
     }
 
+    /// Return a new geometry reprojected from `from` to `to`, building the transform on the fly.
+    /// Handy for one-off reprojections, create a CoordTransform once and use `transform` in loops.
+    /// Unlike `transform_to`, the geometry does not need a spatial reference assigned
+    pub fn transform_between(&self, from: &SpatialRef, to: &SpatialRef) -> Result<Geometry> {
+        self.transform(&CoordTransform::new(from, to)?)
+    }
+
+    /// Exchanges x and y of every coordinate, i.e. turns latitude, longitude into longitude, latitude
+    pub fn swap_xy(&mut self) {
+        unsafe { gdal_sys::OGR_G_SwapXY(self.c_geometry) };
+    }
+
     pub fn area(&self) -> f64 {
         unsafe { gdal_sys::OGR_G_Area(self.c_geometry) }
     }
 
+    /// Length in meters on the ellipsoid of `srs`, which must be the geographic SRS of the
+    /// coordinates.  Lines are measured along their geodesic segments (Vincenty), polygons
+    /// give the length of all their rings.  OGR_G_GeodesicLength needs GDAL 3.10
+    pub fn geodesic_length(&self, srs: &SpatialRef) -> Result<f64> {
+        if !srs.is_geographic() {
+            bail!("geodesic_length needs a geographic SRS, got {}", srs);
+        }
+        let semi_major = srs.semi_major()?;
+        let inv_flattening = srs.inverse_flattening()?;
+        let flattening = if inv_flattening == 0.0 { 0.0 } else { 1.0 / inv_flattening };
+        let to_radians = srs.angular_units();
+        let lat_long = srs.is_lat_long_order();
+
+        Ok(self.sum_geodesic_length(&|p: [f64; 2]| {
+            let (lon, lat) = if lat_long { (p[1], p[0]) } else { (p[0], p[1]) };
+            (lon * to_radians, lat * to_radians)
+        }, semi_major, flattening))
+    }
+
+    fn sum_geodesic_length(&self, to_lon_lat: &dyn Fn([f64; 2]) -> (f64, f64), semi_major: f64, flattening: f64) -> f64 {
+        let sub_count = self.geometry_count();
+        if sub_count > 0 {
+            return (0..sub_count)
+                .map(|n| self.get_geometry(n).sum_geodesic_length(to_lon_lat, semi_major, flattening))
+                .sum();
+        }
+
+        let pts: Vec<(f64, f64)> = self.get_point_vec().into_iter().map(to_lon_lat).collect();
+        pts.windows(2)
+            .map(|w| geodesic_distance(w[0], w[1], semi_major, flattening))
+            .sum()
+    }
+
     /// May or may not contain a reference to a SpatialRef: if not, it returns
     /// an `Ok(None)`; if it does, it tries to build a SpatialRef. If that
     /// succeeds, it returns an Ok(Some(SpatialRef)), otherwise, you get the
@@ -674,6 +1025,26 @@ impl Clone for Geometry {
     }
 }
 
+impl fmt::Display for Geometry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.wkt() {
+            Ok(wkt) => write!(f, "{}", wkt),
+            Err(_) => write!(f, "<invalid geometry>"),
+        }
+    }
+}
+
+impl fmt::Debug for Geometry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Geometry")
+            .field("type", &self.geometry_name())
+            .field("point_count", &self.point_count())
+            .field("geometry_count", &self.geometry_count())
+            .field("wkt", &format!("{}", self))
+            .finish()
+    }
+}
+
 /// Geometry that depends on an existing layer
 /// Thus no Drop is needed and why we keep a reference to the feature
 /// Layer 'l lifetime must at least be as long as the feature lifetime
@@ -730,6 +1101,61 @@ impl Iterator for PointIterator {
     }
 }
 
+/// Distance on the ellipsoid between two (lon, lat) points in radians with Vincenty's inverse
+/// formula.  It doesn't converge for nearly antipodal points, those fall back to the great circle
+/// distance on the sphere of the same mean radius
+fn geodesic_distance(p1: (f64, f64), p2: (f64, f64), a: f64, f: f64) -> f64 {
+    let b = (1.0 - f) * a;
+    let (lon1, lat1) = p1;
+    let (lon2, lat2) = p2;
+    let l = lon2 - lon1;
+    let u1 = ((1.0 - f) * lat1.tan()).atan();
+    let u2 = ((1.0 - f) * lat2.tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // same point
+            return 0.0;
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+        // on the equator cos_sq_alpha is 0
+        let cos_2sigma_m = if cos_sq_alpha != 0.0 {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        } else {
+            0.0
+        };
+        let c = f / 16.0 * cos_sq_alpha * (4.0 + f * (4.0 - 3.0 * cos_sq_alpha));
+        let lambda_prev = lambda;
+        lambda = l + (1.0 - c) * f * sin_alpha
+            * (sigma + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+
+        if (lambda - lambda_prev).abs() < 1e-12 {
+            let u_sq = cos_sq_alpha * (a * a - b * b) / (b * b);
+            let big_a = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = big_b * sin_sigma * (cos_2sigma_m + big_b / 4.0
+                * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                    - big_b / 6.0 * cos_2sigma_m * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                        * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+            return b * big_a * (sigma - delta_sigma);
+        }
+    }
+
+    let mean_radius = (2.0 * a + b) / 3.0;
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * mean_radius * h.sqrt().asin()
+}
+
 #[cfg(test)]
 mod tests {
     use super::Geometry;
@@ -761,6 +1187,217 @@ mod tests {
         assert!(!geom.is_empty());
     }
 
+    #[test]
+    pub fn test_display() {
+        let geom = Geometry::from_wkt("POINT(1 2)").unwrap();
+        assert_eq!(format!("{}", geom), "POINT (1 2)");
+
+        let debug = format!("{:?}", geom);
+        assert!(debug.contains("POINT"));
+        assert!(debug.contains("point_count: 1"));
+    }
+
+    #[test]
+    pub fn test_type_name() {
+        let geom = Geometry::from_wkt("MULTILINESTRING ((0 0, 1 1))").unwrap();
+        assert_eq!(geom.type_name(), "MULTILINESTRING");
+    }
+
+    #[test]
+    pub fn test_coordinate_dimension() {
+        let mut geom = Geometry::from_wkt("POINT Z (1 2 3)").unwrap();
+        assert_eq!(geom.coordinate_dimension(), 3);
+        geom.set_coordinate_dimension(2);
+        assert_eq!(geom.coordinate_dimension(), 2);
+        assert_eq!(geom.wkt().unwrap(), "POINT (1 2)");
+    }
+
+    #[test]
+    pub fn test_from_wkt_dimension_tags() {
+        let geom = Geometry::from_wkt("POINT Z (1 2 3)").unwrap();
+        assert_eq!(geom.coordinate_dimension(), 3);
+        assert!(!geom.is_measured());
+        assert_eq!(geom.get_point_xyz(0), (1.0, 2.0, 3.0));
+
+        let geom = Geometry::from_wkt("MULTIPOLYGON Z (((0 0 1,0 1 1,1 1 2,0 0 1)))").unwrap();
+        assert_eq!(geom.coordinate_dimension(), 3);
+        assert_eq!(geom.geometry_type(), ::gdal_sys::OGRwkbGeometryType::wkbMultiPolygon25D);
+
+        let geom = Geometry::from_wkt("LINESTRING M (0 0 5,1 1 6)").unwrap();
+        assert_eq!(geom.coordinate_dimension(), 2);
+        assert!(geom.is_measured());
+
+        let geom = Geometry::from_wkt("LINESTRING ZM (0 0 1 5,1 1 2 6)").unwrap();
+        assert_eq!(geom.coordinate_dimension(), 3);
+        assert!(geom.is_measured());
+        assert_eq!(geom.get_z_values(), vec![1.0, 2.0]);
+
+        assert!(Geometry::from_wkt("POINT Q (1 2)").is_err());
+    }
+
+    #[test]
+    pub fn test_equals() {
+        let a = Geometry::from_wkt("POLYGON ((0 0, 0 1, 1 1, 1 0, 0 0))").unwrap();
+        let b = Geometry::from_wkt("POLYGON ((0 1, 1 1, 1 0, 0 0, 0 1))").unwrap();
+        assert!(a.equals(&b));
+        assert!(!a.equals_with_tolerance(&b, 1e-6));
+
+        let c = Geometry::from_wkt("POLYGON ((0 0, 0 1.0000000001, 1 1, 1 0, 0 0))").unwrap();
+        assert!(a.equals_with_tolerance(&c, 1e-6));
+        assert!(!a.equals_with_tolerance(&c, 1e-12));
+    }
+
+    #[test]
+    pub fn test_get_z_values() {
+        let geom = Geometry::from_wkt("LINESTRING Z (0 0 10, 1 1 12.5, 2 2 11)").unwrap();
+        assert_eq!(geom.get_z_values(), vec![10.0, 12.5, 11.0]);
+
+        let geom = Geometry::from_wkt("LINESTRING (0 0, 1 1)").unwrap();
+        assert_eq!(geom.get_z_values(), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    pub fn test_normalize_rings() {
+        let wkt = "POLYGON ((0 0,10 0,10 10,0 10,0 0),(2 2,2 4,4 4,4 2,2 2))";
+        let mut geom = Geometry::from_wkt(wkt).unwrap();
+        geom.normalize_rings();
+        assert_eq!(
+            geom.wkt().unwrap(),
+            "POLYGON ((0 0,0 10,10 10,10 0,0 0),(2 2,4 2,4 4,2 4,2 2))"
+        );
+
+        // already oriented, nothing changes
+        let wkt = "MULTIPOLYGON (((0 0,0 10,10 10,10 0,0 0)))";
+        let mut geom = Geometry::from_wkt(wkt).unwrap();
+        geom.normalize_rings();
+        assert_eq!(geom.wkt().unwrap(), wkt);
+    }
+
+    #[test]
+    pub fn test_get_linear_geometry() {
+        let geom = Geometry::from_wkt("CIRCULARSTRING (0 0,1 1,2 0)").unwrap();
+        assert!(geom.has_curve_geometry(true));
+
+        let linear = geom.get_linear_geometry(0.0).unwrap();
+        assert!(!linear.has_curve_geometry(false));
+        assert_eq!(linear.geometry_type(), ::gdal_sys::OGRwkbGeometryType::wkbLineString);
+
+        let coarse = geom.get_linear_geometry(45.0).unwrap();
+        assert!(coarse.point_count() > 2);
+        assert!(coarse.point_count() < linear.point_count());
+        assert_eq!(coarse.get_point(0), [0.0, 0.0]);
+        assert_eq!(coarse.get_point(coarse.point_count() as i32 - 1), [2.0, 0.0]);
+    }
+
+    #[test]
+    pub fn test_get_curve_geometry() {
+        let geom = Geometry::from_wkt("CIRCULARSTRING (0 0,1 1,2 0)").unwrap();
+        let linear = geom.get_linear_geometry(0.0).unwrap();
+
+        let curve = linear.get_curve_geometry().unwrap();
+        assert!(curve.has_curve_geometry(false));
+        assert_eq!(curve.geometry_type(), ::gdal_sys::OGRwkbGeometryType::wkbCircularString);
+        assert_eq!(curve.point_count(), 3);
+        assert_eq!(curve.get_point(0), [0.0, 0.0]);
+        assert_eq!(curve.get_point(2), [2.0, 0.0]);
+
+        // plain lines stay lines
+        let line = Geometry::from_wkt("LINESTRING (0 0,1 0,2 0)").unwrap();
+        let curve = line.get_curve_geometry().unwrap();
+        assert_eq!(curve.geometry_type(), ::gdal_sys::OGRwkbGeometryType::wkbLineString);
+    }
+
+    #[test]
+    pub fn test_sub_geometries() {
+        let geom = Geometry::from_wkt("MULTIPOLYGON (((0 0,1 0,1 1,0 0)),((5 5,7 5,7 7,5 5)))").unwrap();
+        let areas: Vec<f64> = geom.sub_geometries().map(|p| p.area()).collect();
+        assert_eq!(areas, vec![0.5, 2.0]);
+
+        let geom = Geometry::from_wkt("GEOMETRYCOLLECTION (POINT (1 2),MULTIPOINT ((0 0),(1 1)))").unwrap();
+        let types: Vec<_> = geom.sub_geometries().map(|g| g.geometry_type()).collect();
+        assert_eq!(types, vec![
+            ::gdal_sys::OGRwkbGeometryType::wkbPoint,
+            ::gdal_sys::OGRwkbGeometryType::wkbMultiPoint,
+        ]);
+
+        let point = Geometry::from_wkt("POINT (1 2)").unwrap();
+        assert_eq!(point.sub_geometries().count(), 0);
+    }
+
+    #[test]
+    pub fn test_all_coords() {
+        let geom = Geometry::from_wkt("MULTIPOLYGON (((0 0, 4 0, 4 4, 0 0), (1 1, 2 1, 2 2, 1 1)), ((10 10, 11 10, 11 11, 10 10)))").unwrap();
+        let coords = geom.all_coords();
+        assert_eq!(coords.len(), 12);
+        assert_eq!(coords[0], [0.0, 0.0, 0.0]);
+        assert_eq!(coords[4], [1.0, 1.0, 0.0]);
+        assert_eq!(coords[11], [10.0, 10.0, 0.0]);
+
+        let geom = Geometry::from_wkt("GEOMETRYCOLLECTION (POINT Z (1 2 3), LINESTRING Z (0 0 1, 1 1 2))").unwrap();
+        assert_eq!(geom.all_coords(), vec![[1.0, 2.0, 3.0], [0.0, 0.0, 1.0], [1.0, 1.0, 2.0]]);
+
+        assert!(Geometry::empty(::gdal_sys::OGRwkbGeometryType::wkbPolygon).unwrap().all_coords().is_empty());
+    }
+
+    #[test]
+    pub fn test_polygons_only() {
+        let wkt = "GEOMETRYCOLLECTION (POINT (0 0), LINESTRING (0 0, 1 1), POLYGON ((0 0, 0 1, 1 1, 0 0)), \
+            MULTIPOLYGON (((5 5, 5 6, 6 6, 5 5)), ((8 8, 8 9, 9 9, 8 8))))";
+        let geom = Geometry::from_wkt(wkt).unwrap();
+        let polygons = geom.polygons_only().unwrap();
+        assert_eq!(polygons.geometry_type(), ::gdal_sys::OGRwkbGeometryType::wkbMultiPolygon);
+        assert_eq!(polygons.geometry_count(), 3);
+
+        let geom = Geometry::from_wkt("GEOMETRYCOLLECTION (POINT (0 0), POINT (1 1))").unwrap();
+        let polygons = geom.polygons_only().unwrap();
+        assert!(polygons.is_empty());
+    }
+
+    #[test]
+    pub fn test_union_all() {
+        let geoms = vec![
+            Geometry::bbox(0.0, 0.0, 2.0, 2.0).unwrap(),
+            Geometry::bbox(1.0, 1.0, 3.0, 3.0).unwrap(),
+            Geometry::from_wkt("MULTIPOLYGON (((10 10, 10 11, 11 11, 11 10, 10 10)))").unwrap(),
+            Geometry::from_wkt("LINESTRING (0 0, 5 5)").unwrap(),
+        ];
+        let union = Geometry::union_all(&geoms).unwrap();
+        assert_eq!(union.geometry_count(), 2);
+        assert!((union.area() - 8.0).abs() < 1e-9);
+
+        let union = Geometry::union_all(&[Geometry::from_x_y(1.0, 1.0).unwrap()]).unwrap();
+        assert!(union.is_empty());
+    }
+
+    #[test]
+    pub fn test_envelopes_intersect() {
+        let a = Geometry::bbox(0.0, 0.0, 1.0, 1.0).unwrap();
+        let b = Geometry::from_wkt("LINESTRING (0.5 2, 2 0.5)").unwrap();
+        let c = Geometry::bbox(5.0, 5.0, 6.0, 6.0).unwrap();
+        // envelopes overlap even though the geometries do not
+        assert!(a.envelopes_intersect(&b));
+        assert!(!a.intersects(&b));
+        assert!(!a.envelopes_intersect(&c));
+    }
+
+    #[test]
+    pub fn test_exterior_coords() {
+        let wkt = "POLYGON ((0 0, 0 1, 1 1, 1 0, 0 0), (0.2 0.2, 0.2 0.4, 0.4 0.4, 0.2 0.2))";
+        let geom = Geometry::from_wkt(wkt).unwrap();
+        let coords = geom.exterior_coords().unwrap();
+        assert_eq!(coords.len(), 5);
+        assert_eq!(coords[2].x, 1.0);
+        assert_eq!(coords[2].y, 1.0);
+
+        let geom = Geometry::from_wkt("POLYGON EMPTY").unwrap();
+        assert!(geom.exterior_coords().unwrap().is_empty());
+
+        let geom = Geometry::from_wkt("POINT(0 0)").unwrap();
+        assert!(geom.exterior_coords().is_err());
+        let geom = Geometry::from_wkt("MULTIPOLYGON (((0 0, 0 1, 1 1, 0 0)))").unwrap();
+        assert!(geom.exterior_coords().is_err());
+    }
+
     #[test]
     pub fn test_spatial_reference() {
         let geom = Geometry::empty(::gdal_sys::OGRwkbGeometryType::wkbMultiPolygon).unwrap();
@@ -777,4 +1414,37 @@ mod tests {
         geom.set_spatial_reference(&srs);
         assert!(geom.spatial_reference().is_some());
     }
+
+    /// 10 x 10 shell with a 5 x 5 hole sticking half out of it
+    const HOLE_OUTSIDE: &str = "POLYGON ((0 0,10 0,10 10,0 10,0 0),(5 5,15 5,15 15,5 15,5 5))";
+
+    #[test]
+    pub fn test_make_valid_linework() {
+        use super::MakeValidMethod;
+
+        let geom = Geometry::from_wkt(HOLE_OUTSIDE).unwrap();
+        assert!(!geom.is_valid());
+
+        // every ring is linework: the part of the hole outside the shell becomes a polygon
+        let valid = geom.make_valid_ext(MakeValidMethod::Linework).unwrap();
+        assert!(valid.is_valid());
+        assert!((valid.area() - 150.0).abs() < 1e-9);
+    }
+
+    #[test]
+    pub fn test_make_valid_structure() {
+        use super::MakeValidMethod;
+
+        let geom = Geometry::from_wkt(HOLE_OUTSIDE).unwrap();
+
+        #[cfg(feature = "gdal_3_4")]
+        {
+            // the shell minus the hole
+            let valid = geom.make_valid_ext(MakeValidMethod::Structure).unwrap();
+            assert!(valid.is_valid());
+            assert!((valid.area() - 75.0).abs() < 1e-9);
+        }
+        #[cfg(not(feature = "gdal_3_4"))]
+        assert!(geom.make_valid_ext(MakeValidMethod::Structure).is_err());
+    }
 }
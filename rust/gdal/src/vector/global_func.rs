@@ -15,11 +15,13 @@ GNU General Public License for more details.
 You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
-use gdal_sys::{GDALVectorTranslateOptionsNew, GDALVectorTranslateOptionsFree, GDALVectorTranslate, GDALDatasetH, GDALOpenEx};
+use gdal_sys::{GDALVectorTranslateOptionsNew, GDALVectorTranslateOptionsFree, GDALVectorTranslateOptionsSetProgress, GDALVectorTranslate, GDALDatasetH, GDALOpenEx, GDALClose};
 use std::ptr::null_mut;
 use std::fmt::Debug;
 use std::ffi::CString;
 use crate::utils::_last_null_pointer_err;
+use log::debug;
+use std::panic::{self, AssertUnwindSafe};
 
 use anyhow::Result;
 
@@ -33,14 +35,39 @@ pub const GDAL_OF_KIND_MASK : u32 = 0x1E;
 pub const GDAL_OF_SHARED : u32 = 0x20;
 pub const GDAL_OF_VERBOSE_ERROR: u32 = 0x40;
 
+/// Progress reporting for long running operations, called with the completed fraction (0 to 1).
+/// Returning false cancels the operation
+pub type ProgressCallback<'a> = dyn FnMut(f64) -> bool + 'a;
+
+unsafe extern "C" fn progress_trampoline(complete: f64, _message: *const libc::c_char, progress_arg: *mut libc::c_void) -> libc::c_int {
+	let progress = &mut *(progress_arg as *mut &mut ProgressCallback);
+	// a panic must not unwind across GDAL, treat it as a cancellation
+	match panic::catch_unwind(AssertUnwindSafe(|| progress(complete))) {
+		Ok(true) => 1,
+		_ => 0,
+	}
+}
+
 pub fn translate<T>(src: &str, dst: &str, options: &[ T ]) -> Result<()>
 where T: AsRef<str> + Debug
+{
+	translate_with_progress(src, dst, options, None)
+}
+
+/// Same as translate, reporting progress to `progress`.  When it cancels, the translation
+/// stops with an error; the features already written to dst are flushed and dst is closed,
+/// so it can be opened (or deleted) afterwards
+pub fn translate_with_progress<T>(src: &str, dst: &str, options: &[ T ], mut progress: Option<&mut ProgressCallback>) -> Result<()>
+where T: AsRef<str> + Debug
 {
 
 	unsafe {
-		println!("Calling ogr2ogr / vector translate to {:?} with options {:?}", dst, options);
+		debug!("Calling ogr2ogr / vector translate to {:?} with options {:?}", dst, options);
 
 		let src_cstr = CString::new(src)?;
+		let dst_cstr = CString::new(dst)?;
+		//do this locally since we don't want the CStrings to be deallocated until this function ends
+		let c_strings = options.iter().map(|s| CString::new(s.as_ref())).collect::<std::result::Result<Vec<_>, _>>()?;
 
 		let src_ds = GDALOpenEx(src_cstr.as_ptr(),
                          GDAL_OF_VECTOR, null_mut(),
@@ -50,8 +77,6 @@ where T: AsRef<str> + Debug
 		vec_ds.push(src_ds);
 		vec_ds.push(0 as GDALDatasetH);
 
-		//do this locally since we don't want the CStrings to be deallocated until this function ends
-		let c_strings: Vec<CString> = options.into_iter().map(|s| CString::new(s.as_ref()).unwrap()).collect();
 		//Need the strings as const* const* i8 for gdal, so just cast the char* string (both are 1 byte)
 		let mut c_as_i8: Vec<*mut libc::c_char> = c_strings.iter().map(|cs| cs.as_ptr() as *mut libc::c_char).collect();
 
@@ -65,14 +90,23 @@ where T: AsRef<str> + Debug
 		);
 
 		if ogr2ogr_options.is_null() {
-			println!("Error with rasterize options");
-            Err(_last_null_pointer_err("GDALVectorTranslateOptionsNew"))?;
-        }
+			let err = _last_null_pointer_err("GDALVectorTranslateOptionsNew");
+			if !src_ds.is_null() {
+				GDALClose(src_ds);
+			}
+			Err(err)?;
+		}
+
+		if let Some(progress) = progress.as_mut() {
+			GDALVectorTranslateOptionsSetProgress(
+				ogr2ogr_options,
+				Some(progress_trampoline),
+				progress as *mut &mut ProgressCallback as *mut libc::c_void
+			);
+		}
 
 		let mut usage_error: i32 = 0;
 
-        let dst_cstr = CString::new(dst)?;
-
 		//println!("GDALVectorTranslate");
 		let c_dataset = GDALVectorTranslate(
 			dst_cstr.as_ptr(),
@@ -89,19 +123,20 @@ where T: AsRef<str> + Debug
 		GDALVectorTranslateOptionsFree(ogr2ogr_options);
 		//println!("Done GDAL options free");
 
-		if c_dataset.is_null() {
-			println!("Error with ogr2ogr");
-            Err(_last_null_pointer_err("GDALVectorTranslate"))?;
-        }
-
-
-		//assert_eq!(c_dataset, dst._c_ptr(), "Returned dataset should be the same as the opened dst dataset");
-
-		//Note, because these are the same, when dst is dropped at the end of this function, the dataset will be closed properly
-		//thus there is no need to call gdal_sys::GDALClose(c_dataset);
+		// on failure or cancellation GDAL has already closed dst; otherwise closing it flushes
+		// the written features
+		let rv = if c_dataset.is_null() {
+			Err(_last_null_pointer_err("GDALVectorTranslate"))
+		} else {
+			GDALClose(c_dataset);
+			Ok(())
+		};
+		if !src_ds.is_null() {
+			GDALClose(src_ds);
+		}
 
 		//println!("Returning");
-        Ok(())
+        Ok(rv?)
 	}
 
 }
\ No newline at end of file
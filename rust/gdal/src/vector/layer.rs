@@ -17,13 +17,14 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 use crate::gdal_major_object::MajorObject;
 use crate::metadata::Metadata;
-use crate::spatial_ref::SpatialRef;
+use crate::spatial_ref::{CoordTransform, SpatialRef};
 use crate::utils::{_last_null_pointer_err, _string};
 use crate::vector::layer_definition::LayerDefinition;
-use crate::vector::{Feature, Geometry, Field, Dataset, FieldValue};
+use crate::vector::{Feature, Geometry, Field, Dataset, Extent, FieldValue};
 use gdal_sys::{
-    self, GDALMajorObjectH, OGREnvelope, OGRErr, OGRFieldType, OGRLayerH,
+    self, GDALMajorObjectH, OGREnvelope, OGRErr, OGRFieldType, OGRLayerH, OGRwkbGeometryType,
 };
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::ptr::null_mut;
 
@@ -33,6 +34,7 @@ use crate::vector::field::{FieldDefinition, GeomField};
 
 /// Layer in a vector dataset.  This is an existing layer that will live shorter than the dataset
 ///
+/// Layer metadata (`metadata_item` / `set_metadata_item`) comes from the Metadata trait
 
 pub struct Layer<'d> {
     pub (crate) c_layer: OGRLayerH,
@@ -58,6 +60,15 @@ impl <'d> Drop for Layer<'d> {
         }
     }
 }
+/// Flags for `Layer::alter_field`, which parts of the field definition to change
+pub const ALTER_NAME_FLAG: i32 = 0x1;
+pub const ALTER_TYPE_FLAG: i32 = 0x2;
+pub const ALTER_WIDTH_PRECISION_FLAG: i32 = 0x4;
+pub const ALTER_NULLABLE_FLAG: i32 = 0x8;
+pub const ALTER_DEFAULT_FLAG: i32 = 0x10;
+pub const ALTER_ALL_FLAG: i32 = ALTER_NAME_FLAG | ALTER_TYPE_FLAG | ALTER_WIDTH_PRECISION_FLAG
+    | ALTER_NULLABLE_FLAG | ALTER_DEFAULT_FLAG;
+
 //
 // mod capability_constants {
 //     const OLC_CREATE_FIELD: &str = "CreateField";
@@ -95,11 +106,244 @@ impl <'a> Layer<'a> {
         Ok(r_int == 1)
     }
 
+    /// Errors with ErrorKind::UnsupportedCapability when the driver doesn't support `capability`
+    fn require_capability(&self, capability: &'static str) -> Result<()> {
+        if !self.test_capability(capability)? {
+            Err(ErrorKind::UnsupportedCapability {
+                capability,
+                layer_name: self.name(),
+            })?;
+        }
+        Ok(())
+    }
+
     /// Iterate over all features in this layer.
     pub fn features(&self) -> FeatureIterator {
         FeatureIterator::_with_layer(self)
     }
 
+    /// Iterate over the features matching an attribute filter (OGR SQL where clause) and
+    /// optionally a spatial filter.  The filtering is done by GDAL, so indexes of the
+    /// datasource are used.  When the iterator is dropped the attribute filter is cleared (GDAL
+    /// has no getter for the previous one) and, if `spatial_filter` was given, the previous spatial
+    /// filter is put back.  With None the spatial filter already set on the layer applies.
+    pub fn filtered_features(&self, attribute_filter: &str, spatial_filter: Option<&Geometry>) -> Result<FeatureIterator> {
+        let c_filter = CString::new(attribute_filter)?;
+        let rv = unsafe { gdal_sys::OGR_L_SetAttributeFilter(self.c_layer, c_filter.as_ptr()) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OGR_L_SetAttributeFilter",
+            })?;
+        }
+
+        let restore_spatial_filter = spatial_filter.map(|geometry| {
+            let previous = unsafe { gdal_sys::OGR_L_GetSpatialFilter(self.c_layer) };
+            // the layer owns the previous filter and frees it when it is replaced
+            let previous = if previous.is_null() {
+                None
+            } else {
+                Some(unsafe { Geometry::with_c_geometry(gdal_sys::OGR_G_Clone(previous), true) })
+            };
+            self.set_spatial_filter(geometry);
+            previous
+        });
+
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+
+        Ok(FeatureIterator {
+            layer: self,
+            clear_attribute_filter_on_drop: true,
+            restore_spatial_filter,
+            swap_xy: false,
+        })
+    }
+
+    /// Split the layer into `n` FID ranges `[start, end)` of about the same feature count,
+    /// for processing a layer in parallel.
+    ///
+    /// GDAL objects are not thread safe, each worker must open its own Dataset and read
+    /// its range with `features_in_fid_range`.  The ranges assume FIDs are mostly contiguous
+    /// (shapefiles, geopackages), gaps only make the ranges less balanced.
+    pub fn feature_ranges(&self, n: usize) -> Vec<(u64, u64)> {
+        let count = self.count(true).max(0) as u64;
+        if count == 0 || n == 0 {
+            return Vec::new();
+        }
+
+        let first_fid = unsafe {
+            gdal_sys::OGR_L_ResetReading(self.c_layer);
+            let c_feature = gdal_sys::OGR_L_GetNextFeature(self.c_layer);
+            let fid = if c_feature.is_null() { 0 } else { gdal_sys::OGR_F_GetFID(c_feature) };
+            if !c_feature.is_null() {
+                gdal_sys::OGR_F_Destroy(c_feature);
+            }
+            gdal_sys::OGR_L_ResetReading(self.c_layer);
+            fid.max(0) as u64
+        };
+
+        let n = (n as u64).min(count);
+        let chunk_size = (count + n - 1) / n;
+
+        (0..n).map(|i| {
+            let start = first_fid + i * chunk_size;
+            let end = (first_fid + (i + 1) * chunk_size).min(first_fid + count);
+            (start, end)
+        }).filter(|(start, end)| start < end).collect()
+    }
+
+    /// Iterate over the features with `start <= FID < end`, see `feature_ranges`
+    pub fn features_in_fid_range(&self, start: u64, end: u64) -> Result<FeatureIterator> {
+        self.filtered_features(&format!("FID >= {} AND FID < {}", start, end), None)
+    }
+
+    /// Number of features per geometry type, features without a geometry are counted as wkbNone.
+    /// Reads the whole layer (respecting the current filters)
+    pub fn geometry_type_histogram(&self) -> Result<HashMap<OGRwkbGeometryType::Type, u64>> {
+        let mut histogram = HashMap::new();
+
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+        for feature in self.features() {
+            let geometry = feature.geometry().as_geom();
+            let geometry_type = if geometry.c_geometry.is_null() {
+                OGRwkbGeometryType::wkbNone
+            } else {
+                geometry.geometry_type()
+            };
+            *histogram.entry(geometry_type).or_insert(0) += 1;
+        }
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+
+        Ok(histogram)
+    }
+
+    /// Sum of the areas of all (multi)polygon features, measured after reprojecting each geometry to
+    /// `measure_srs`.  Other geometry types are skipped.  The result is in the square units of `measure_srs`
+    pub fn total_area(&self, measure_srs: &SpatialRef) -> Result<f64> {
+        let transform = CoordTransform::new(&self.spatial_reference()?, measure_srs)?;
+        let mut total = 0.0;
+
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+        for feature in self.features() {
+            let geometry = feature.geometry().as_geom();
+            if geometry.c_geometry.is_null() {
+                continue;
+            }
+            let flat_type = unsafe { gdal_sys::OGR_GT_Flatten(geometry.geometry_type()) };
+            if flat_type != OGRwkbGeometryType::wkbPolygon && flat_type != OGRwkbGeometryType::wkbMultiPolygon {
+                continue;
+            }
+            total += geometry.transform(&transform)?.area();
+        }
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+
+        Ok(total)
+    }
+
+    /// min, max, mean of a numeric (integer or real) field in one pass over the layer
+    /// (respecting the current filters), errors for other field types
+    pub fn field_stats(&self, field: &str) -> Result<FieldStats> {
+        let field_idx = self.layer_definition().get_numeric_field_index(field)?;
+
+        let mut stats = FieldStats::default();
+        let mut sum = 0.0;
+
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+        for feature in self.features() {
+            if !feature.is_field_set_and_not_null(field_idx) {
+                stats.null_count += 1;
+                continue;
+            }
+            let value = feature.get_field_as_real(field_idx);
+            stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+            stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+            sum += value;
+            stats.count += 1;
+        }
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+
+        if stats.count > 0 {
+            stats.mean = Some(sum / stats.count as f64);
+        }
+        Ok(stats)
+    }
+
+    /// Delete the features matching the attribute filter, returns how many were deleted.
+    /// Any attribute or spatial filter set on the layer is cleared
+    pub fn delete_features_where(&self, filter: &str) -> Result<u64> {
+        let fids: Vec<i64> = self.filtered_features(filter, None)?.map(|f| f.fid()).collect();
+
+        for fid in fids.iter() {
+            self.delete_feature(*fid)?;
+        }
+
+        Ok(fids.len() as u64)
+    }
+
+    pub fn delete_feature(&self, fid: i64) -> Result<()> {
+        let rv = unsafe { gdal_sys::OGR_L_DeleteFeature(self.c_layer, fid) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OGR_L_DeleteFeature",
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn rename_field(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let layer_defn = self.layer_definition();
+        let field_type = layer_defn.get_field(layer_defn.get_field_index(old_name)?).field_type();
+        let new_defn = FieldDefinition::new(new_name, field_type)?;
+        self.alter_field(old_name, &new_defn, ALTER_NAME_FLAG)
+    }
+
+    /// Changes the field `name` to `new_defn`, `flags` (ALTER_*_FLAG) say which parts of it are used
+    pub fn alter_field(&self, name: &str, new_defn: &FieldDefinition, flags: i32) -> Result<()> {
+        self.require_capability("AlterFieldDefn")?;
+        let field_idx = self.layer_definition().get_field_index(name)?;
+        let rv = unsafe {
+            gdal_sys::OGR_L_AlterFieldDefn(self.c_layer, field_idx, new_defn.c_obj, flags)
+        };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OGR_L_AlterFieldDefn",
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn delete_field(&self, name: &str) -> Result<()> {
+        self.require_capability("DeleteField")?;
+        let field_idx = self.layer_definition().get_field_index(name)?;
+        let rv = unsafe { gdal_sys::OGR_L_DeleteField(self.c_layer, field_idx) };
+        if rv != OGRErr::OGRERR_NONE {
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OGR_L_DeleteField",
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Iterate over all features with their geometries in longitude, latitude order.  When the
+    /// layer SRS puts latitude first (authority compliant axis order, see
+    /// `SpatialRef::is_lat_long_order`) x / y of every geometry are swapped as the features are
+    /// read, otherwise the features are returned as they are.  Nothing is written to the dataset,
+    /// so it can be called any number of times; write the features to another layer to keep them
+    pub fn normalize_axis_order(&self) -> Result<FeatureIterator> {
+        let swap_xy = self.spatial_reference()?.is_lat_long_order();
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+
+        Ok(FeatureIterator {
+            layer: self,
+            clear_attribute_filter_on_drop: false,
+            restore_spatial_filter: None,
+            swap_xy,
+        })
+    }
+
     pub fn get_feature_by_id<'l, 'd>(&'d self, fid: i64) -> Result<Feature<'l, 'd>>
     {
         unsafe {
@@ -129,12 +373,18 @@ impl <'a> Layer<'a> {
         }
     }
 
+    /// Same as `set_spatial_filter_rect`, taking the bounds as a `geo_types::Rect`
+    pub fn set_spatial_filter_geo(&self, rect: geo_types::Rect<f64>) {
+        let (min, max) = (rect.min(), rect.max());
+        self.set_spatial_filter_rect(min.x, min.y, max.x, max.y);
+    }
+
     pub fn set_feature(&self, feature: &Feature) -> Result<()> {
         unsafe {
             let rv = gdal_sys::OGR_L_SetFeature(self.c_layer, feature.c_feature);
             if rv != OGRErr::OGRERR_NONE {
                 Err(ErrorKind::OgrError {
-                    err: rv,
+                    err: rv.into(),
                     method_name: "OGR_L_SetFeature",
                 })?;
             }
@@ -152,6 +402,42 @@ impl <'a> Layer<'a> {
         unsafe { gdal_sys::OGR_L_SetSpatialFilter(self.c_layer, null_mut()) };
     }
 
+    /// Sets the bounding box (`[min_x, min_y, max_x, max_y]`) and attribute filters together,
+    /// None clears that filter.  If the attribute filter is rejected both filters are cleared,
+    /// so the layer never ends up with only half of the filtering
+    pub fn set_filters(&self, bbox: Option<[f64; 4]>, attribute: Option<&str>) -> Result<()> {
+        let c_filter = attribute.map(CString::new).transpose()?;
+        let rv = unsafe {
+            gdal_sys::OGR_L_SetAttributeFilter(
+                self.c_layer,
+                c_filter.as_ref().map_or(null_mut(), |f| f.as_ptr()),
+            )
+        };
+        if rv != OGRErr::OGRERR_NONE {
+            self.clear_filters();
+            Err(ErrorKind::OgrError {
+                err: rv.into(),
+                method_name: "OGR_L_SetAttributeFilter",
+            })?;
+        }
+
+        match bbox {
+            Some([min_x, min_y, max_x, max_y]) => self.set_spatial_filter_rect(min_x, min_y, max_x, max_y),
+            None => self.clear_spatial_filter(),
+        }
+        unsafe { gdal_sys::OGR_L_ResetReading(self.c_layer) };
+        Ok(())
+    }
+
+    /// Clears both the spatial and the attribute filter
+    pub fn clear_filters(&self) {
+        unsafe {
+            gdal_sys::OGR_L_SetAttributeFilter(self.c_layer, null_mut());
+            gdal_sys::OGR_L_SetSpatialFilter(self.c_layer, null_mut());
+            gdal_sys::OGR_L_ResetReading(self.c_layer);
+        }
+    }
+
     pub fn count(&self, force:bool) -> i64 {
         let fc = unsafe { gdal_sys::OGR_L_GetFeatureCount(self.c_layer, if force {1} else {0}) };
         fc
@@ -177,7 +463,7 @@ impl <'a> Layer<'a> {
         let rv = unsafe { gdal_sys::OGR_L_CreateGeomField(self.c_layer, geom_field.c_field_defn, b_approx_ok) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_L_CreateGeomField",
             })?;
         }
@@ -192,7 +478,7 @@ impl <'a> Layer<'a> {
         let rv = unsafe { gdal_sys::OGR_L_CreateField(self.c_layer, field.c_field_defn, b_approx_ok) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_L_CreateField",
             })?;
         }
@@ -206,14 +492,14 @@ impl <'a> Layer<'a> {
         let rv = unsafe { gdal_sys::OGR_F_SetGeometryDirectly(c_feature, c_geometry) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_F_SetGeometryDirectly",
             })?;
         }
         let rv = unsafe { gdal_sys::OGR_L_CreateFeature(self.c_layer, c_feature) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_L_CreateFeature",
             })?;
         }
@@ -229,7 +515,7 @@ impl <'a> Layer<'a> {
     ) -> Result<()> {
         let layer_def = self.layer_definition();
         let mut ft = Feature::new(&layer_def)?;
-        ft.set_geometry(geometry)?;
+        ft.set_geometry(&geometry)?;
         for (fd, val) in field_names.iter().zip(values.iter()) {
             ft.set_field(fd, val)?;
         }
@@ -237,7 +523,9 @@ impl <'a> Layer<'a> {
         Ok(())
     }
 
-    pub fn get_extent(&self, force: bool) -> Result<gdal_sys::OGREnvelope> {
+    /// Bounding box of the layer.  Without `force`, fails if the driver can't get it cheaply,
+    /// i.e. without scanning every feature
+    pub fn get_extent(&self, force: bool) -> Result<Extent> {
         let mut envelope = OGREnvelope {
             MinX: 0.0,
             MaxX: 0.0,
@@ -248,11 +536,11 @@ impl <'a> Layer<'a> {
         let rv = unsafe { gdal_sys::OGR_L_GetExtent(self.c_layer, &mut envelope, force) };
         if rv != OGRErr::OGRERR_NONE {
             Err(ErrorKind::OgrError {
-                err: rv,
+                err: rv.into(),
                 method_name: "OGR_L_GetExtent",
             })?;
         }
-        Ok(envelope)
+        Ok(envelope.into())
     }
 
     pub fn spatial_reference(&self) -> Result<SpatialRef> {
@@ -264,9 +552,38 @@ impl <'a> Layer<'a> {
     }
 }
 
+/// Summary of a numeric field, see Layer::field_stats.
+/// min, max and mean are None when every value is null
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldStats {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub count: u64,
+    pub null_count: u64,
+}
+
 /// Lifetime of dataset must at least be as long of the layer
 pub struct FeatureIterator<'l, 'd: 'l> {
     layer: &'l Layer<'d>,
+    clear_attribute_filter_on_drop: bool,
+    /// Some when the iterator replaced the spatial filter, with the filter to put back on drop
+    restore_spatial_filter: Option<Option<Geometry>>,
+    /// see Layer::normalize_axis_order
+    swap_xy: bool,
+}
+
+impl<'l, 'd> Drop for FeatureIterator<'l, 'd> {
+    fn drop(&mut self) {
+        if self.clear_attribute_filter_on_drop {
+            unsafe { gdal_sys::OGR_L_SetAttributeFilter(self.layer.c_layer, null_mut()) };
+        }
+        match self.restore_spatial_filter.take() {
+            Some(Some(previous)) => self.layer.set_spatial_filter(&previous),
+            Some(None) => self.layer.clear_spatial_filter(),
+            None => {}
+        }
+    }
 }
 
 impl<'l, 'd> Iterator for FeatureIterator<'l, 'd> {
@@ -278,13 +595,23 @@ impl<'l, 'd> Iterator for FeatureIterator<'l, 'd> {
         if c_feature.is_null() {
             None
         } else {
-            Some(unsafe { Feature::_with_c_feature(&self.layer, c_feature) })
+            let feature = unsafe { Feature::_with_c_feature(&self.layer, c_feature) };
+            if self.swap_xy && feature.has_geometry() {
+                // the geometry is owned by the feature, this swaps it in place
+                feature.geometry().as_geom().swap_xy();
+            }
+            Some(feature)
         }
     }
 }
 
 impl<'l, 'd: 'l> FeatureIterator<'l, 'd> {
     pub fn _with_layer(layer: &'l Layer<'d>) -> FeatureIterator<'l, 'd> {
-        FeatureIterator { layer }
+        FeatureIterator {
+            layer,
+            clear_attribute_filter_on_drop: false,
+            restore_spatial_filter: None,
+            swap_xy: false,
+        }
     }
 }
@@ -16,7 +16,7 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 use crate::vector::layer::Layer;
-use gdal_sys::{self, OGRFeatureDefnH, OGRwkbGeometryType};
+use gdal_sys::{self, OGRFeatureDefnH, OGRFieldType, OGRwkbGeometryType};
 
 use crate::errors::*;
 use std::convert::TryFrom;
@@ -106,6 +106,40 @@ impl <'l, 'd> LayerDefinition<'l, 'd> {
         Ok(idx)
     }
 
+    /// Like get_field_index, but the field must also be numeric (integer, integer64 or real),
+    /// `ErrorKind::NonNumericField` otherwise
+    pub fn get_numeric_field_index(&self, field_name: &str) -> Result<i32> {
+        let idx = self.get_field_index(field_name)?;
+        let field_type = self.get_field(idx).field_type();
+        match field_type {
+            OGRFieldType::OFTInteger | OGRFieldType::OFTInteger64 | OGRFieldType::OFTReal => Ok(idx),
+            _ => Err(ErrorKind::NonNumericField {
+                field_name: field_name.to_string(),
+                field_type,
+                method_name: "get_numeric_field_index",
+            })?,
+        }
+    }
+
+    /// Index of the field named `field_name`, None if the layer has no such field.
+    /// Cheaper than get_field_index as no error message is built, so fine to call
+    /// when probing for optional columns
+    pub fn field_index(&self, field_name: &str) -> Option<usize> {
+        let c_str_field_name = CString::new(field_name).ok()?;
+        let idx =
+            unsafe { gdal_sys::OGR_FD_GetFieldIndex(self.c_defn, c_str_field_name.as_ptr()) };
+
+        usize::try_from(idx).ok()
+    }
+
+    /// Field definition at `field_index`, None if out of range
+    pub fn field(&self, field_index: usize) -> Option<Field> {
+        if field_index >= self.field_count() as usize {
+            return None;
+        }
+        Some(self.get_field(field_index as i32))
+    }
+
     pub fn get_field(&self, field_index: i32) -> Field {
         Field {
             _defn: self,
@@ -21,11 +21,15 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 pub use crate::vector::dataset::Dataset;
 pub use crate::vector::layer_definition::{LayerDefinition, };
-pub use crate::vector::field::{Field, FieldIterator, FieldDefinition, GeomField, geometry_type_to_name, field_type_to_name};
+pub use crate::vector::field::{Field, FieldIterator, FieldDefinition, GeomField, geometry_type_to_name, field_type_to_name, rust_type_name};
 pub use crate::vector::driver::Driver;
-pub use crate::vector::feature::{Feature, FieldValue};
-pub use crate::vector::geometry::{Geometry};
-pub use crate::vector::layer::{FeatureIterator, Layer};
+pub use crate::vector::extent::Extent;
+pub use crate::vector::feature::{Feature, FieldValue, ReadOnlyFeature};
+pub use crate::vector::geometry::{Geometry, MakeValidMethod};
+pub use crate::vector::layer::{
+    FeatureIterator, FieldStats, Layer, ALTER_ALL_FLAG, ALTER_DEFAULT_FLAG, ALTER_NAME_FLAG,
+    ALTER_NULLABLE_FLAG, ALTER_TYPE_FLAG, ALTER_WIDTH_PRECISION_FLAG,
+};
 pub use crate::vector::ops::geometry::intersection::Intersection as GeometryIntersection;
 pub use gdal_sys::{OGRFieldType, OGRFieldSubType, OGRwkbGeometryType, OGREnvelope};
 pub use crate::vector::global_func::*;
@@ -35,6 +39,7 @@ pub use crate::vector::global_func::*;
 mod dataset;
 mod layer_definition;
 mod driver;
+mod extent;
 mod feature;
 mod geometry;
 mod layer;
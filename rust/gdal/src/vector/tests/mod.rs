@@ -16,8 +16,8 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 use super::{
-    Dataset, Driver, Feature, FeatureIterator, FieldValue, Geometry, OGRFieldType,
-    OGRwkbGeometryType,
+    Dataset, Driver, Extent, Feature, FeatureIterator, FieldDefinition, FieldValue, Geometry,
+    OGRFieldType, OGRwkbGeometryType, ALTER_WIDTH_PRECISION_FLAG, rust_type_name,
 };
 use crate::assert_almost_eq;
 use crate::spatial_ref::SpatialRef;
@@ -54,10 +54,21 @@ fn test_layer_extent() {
     let layer = ds.layer(0).unwrap();
     assert!(layer.get_extent(false).is_err());
     let extent = layer.get_extent(true).unwrap();
-    assert_almost_eq(extent.MinX, 26.100768);
-    assert_almost_eq(extent.MaxX, 26.103515);
-    assert_almost_eq(extent.MinY, 44.429858);
-    assert_almost_eq(extent.MaxY, 44.431818);
+    assert_almost_eq(extent.min_x, 26.100768);
+    assert_almost_eq(extent.max_x, 26.103515);
+    assert_almost_eq(extent.min_y, 44.429858);
+    assert_almost_eq(extent.max_y, 44.431818);
+
+    assert_almost_eq(extent.width(), 26.103515 - 26.100768);
+    assert_almost_eq(extent.height(), 44.431818 - 44.429858);
+    let [x, y] = extent.center();
+    assert!(extent.contains_point(x, y));
+    assert!(!extent.contains_point(x, 45.0));
+    assert!(extent.intersects(&Extent::new(26.0, 44.0, 26.101, 44.43)));
+    assert!(!extent.intersects(&Extent::new(26.0, 44.0, 26.1, 44.4)));
+
+    let bbox = extent.to_bbox_geometry().unwrap();
+    assert_almost_eq(bbox.area(), extent.width() * extent.height());
 }
 
 #[test]
@@ -111,6 +122,96 @@ fn test_string_field() {
     });
 }
 
+#[test]
+fn test_filtered_features() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    {
+        let features = layer.filtered_features("highway = 'residential'", None).unwrap();
+        assert_eq!(features.count(), 2);
+    }
+    {
+        let bbox = Geometry::bbox(26.1017, 44.4297, 26.1025, 44.4303).unwrap();
+        let features = layer.filtered_features("highway = 'footway'", Some(&bbox)).unwrap();
+        assert_eq!(features.count(), 4);
+    }
+    // filters are cleared once the iterator is dropped
+    assert_eq!(layer.count(true), 21);
+}
+
+#[test]
+fn test_filtered_features_keeps_spatial_filter() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let bbox = Geometry::bbox(26.1017, 44.4297, 26.1025, 44.4303).unwrap();
+    layer.set_spatial_filter(&bbox);
+    let in_bbox = layer.count(true);
+    assert!(in_bbox < 21);
+
+    // without a spatial filter the one set on the layer applies and stays
+    {
+        let features = layer.filtered_features("highway = 'footway'", None).unwrap();
+        assert_eq!(features.count(), 4);
+    }
+    assert_eq!(layer.count(true), in_bbox);
+
+    // a spatial filter replaces it while iterating, then it is put back
+    {
+        let everywhere = Geometry::bbox(-180.0, -90.0, 180.0, 90.0).unwrap();
+        let features = layer.filtered_features("highway = 'footway'", Some(&everywhere)).unwrap();
+        assert_eq!(features.count(), 8);
+    }
+    assert_eq!(layer.count(true), in_bbox);
+
+    layer.clear_spatial_filter();
+    assert_eq!(layer.count(true), 21);
+}
+
+#[test]
+fn test_feature_ranges() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let ranges = layer.feature_ranges(4);
+    assert_eq!(ranges, vec![(0, 6), (6, 12), (12, 18), (18, 21)]);
+
+    let total: usize = ranges
+        .iter()
+        .map(|(start, end)| layer.features_in_fid_range(*start, *end).unwrap().count())
+        .sum();
+    assert_eq!(total, 21);
+}
+
+#[test]
+fn test_read_only_feature() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let ro_features = layer
+        .features()
+        .map(|f| f.to_read_only().unwrap())
+        .collect::<Vec<_>>();
+
+    let handle = std::thread::spawn(move || {
+        let first = &ro_features[0];
+        match first.field("highway") {
+            Some(FieldValue::StringValue(highway)) => assert_eq!(highway, "footway"),
+            other => panic!("unexpected highway value {:?}", other),
+        }
+        let geom = first.geometry().unwrap().unwrap();
+        assert_eq!(geom.geometry_type(), OGRwkbGeometryType::wkbLineString);
+        ro_features.len()
+    });
+    assert_eq!(handle.join().unwrap(), 21);
+}
+
+#[test]
+fn test_geometry_type_histogram() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let histogram = layer.geometry_type_histogram().unwrap();
+    assert_eq!(histogram.len(), 1);
+    assert_eq!(histogram[&OGRwkbGeometryType::wkbLineString], 21);
+}
+
 #[test]
 fn test_float_field() {
     with_first_feature("roads.geojson", |feature| {
@@ -121,6 +222,35 @@ fn test_float_field() {
     });
 }
 
+#[test]
+fn test_field_as_f64_and_i64() {
+    with_first_feature("roads.geojson", |feature| {
+        assert_almost_eq(feature.field_as_f64("sort_key").unwrap(), -9.0);
+
+        // reals are not truncated to integers
+        let err = feature.field_as_i64("sort_key").unwrap_err();
+        assert!(err.to_string().contains("sort_key"));
+
+        let err = feature.field_as_f64("highway").unwrap_err();
+        assert!(err.to_string().contains("highway"));
+
+        let err = feature.field_as_f64("railway").unwrap_err();
+        assert!(err.to_string().contains("railway"));
+
+        assert!(feature.field_as_f64("no such field").is_err());
+    });
+}
+
+#[test]
+fn test_field_as_string() {
+    with_first_feature("roads.geojson", |feature| {
+        let idx = feature.get_field_index("highway").unwrap();
+        assert_eq!(feature.field_as_string(idx), Some("footway".to_string()));
+        let idx = feature.get_field_index("railway").unwrap();
+        assert_eq!(feature.field_as_string(idx), None);
+    });
+}
+
 #[test]
 fn test_missing_field() {
     with_first_feature("roads.geojson", |feature| {
@@ -142,6 +272,14 @@ fn test_geom_accessors() {
                 [26.1020002, 44.4304202]
             ]
         );
+        assert_eq!(
+            geom.get_point_vec_2d(),
+            vec![
+                (26.1019276, 44.4302748),
+                (26.1019382, 44.4303191),
+                (26.1020002, 44.4304202)
+            ]
+        );
         assert_eq!(geom.geometry_count(), 0);
 
         let geom = feature.geometry_by_index(0).unwrap().as_geom();
@@ -207,6 +345,45 @@ fn test_schema() {
     .map(|s| (s.0.to_string(), s.1))
     .collect::<Vec<_>>();
     assert_eq!(name_list, ok_names_types);
+
+    assert_eq!(rust_type_name(OGRFieldType::OFTReal), "f64");
+    assert_eq!(rust_type_name(OGRFieldType::OFTString), "String");
+    assert_eq!(rust_type_name(OGRFieldType::OFTBinary), "unsupported");
+}
+
+#[test]
+fn test_field_value_from_ogr_field() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let fields: Vec<_> = layer.layer_definition().fields().map(|f| f.field_type()).collect();
+    let feature = layer.features().next().unwrap();
+
+    let values: Vec<FieldValue> = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, &field_type)| FieldValue::from_ogr_field(&feature, idx as i32, field_type).unwrap())
+        .collect();
+    assert!(matches!(&values[1], FieldValue::StringValue(kind) if kind == "path"));
+    assert!(matches!(values[2], FieldValue::RealValue(v) if v == -9.0));
+    assert!(matches!(values[6], FieldValue::Null));
+
+    // read with another type than declared, GDAL converts
+    let sort_key = FieldValue::from_ogr_field(&feature, 2, OGRFieldType::OFTInteger).unwrap();
+    assert_eq!(sort_key.into_int(), Some(-9));
+
+    assert!(FieldValue::from_ogr_field(&feature, 100, OGRFieldType::OFTString).is_err());
+}
+
+#[test]
+fn test_field_index() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let defn = layer.layer_definition();
+    let idx = defn.field_index("sort_key").unwrap();
+    assert_eq!(idx, 2);
+    assert_eq!(defn.field(idx).unwrap().field_type(), OGRFieldType::OFTReal);
+    assert!(defn.field_index("no such field").is_none());
+    assert!(defn.field(100).is_none());
 }
 
 #[test]
@@ -231,6 +408,14 @@ fn test_geom_fields() {
     spatial_ref2.set_axis_mapping_strategy(0);
 
     assert!(geom_field.spatial_ref().unwrap() == spatial_ref2);
+
+    let feature = layer.features().next().unwrap();
+    assert_eq!(feature.geom_field_count(), 1);
+    let (geometry, geometry_type, spatial_ref) = feature.geometry_field(0).unwrap();
+    assert_eq!(geometry.as_geom().geometry_type(), OGRwkbGeometryType::wkbLineString);
+    assert_eq!(geometry_type, OGRwkbGeometryType::wkbLineString);
+    assert!(spatial_ref.unwrap() == spatial_ref2);
+    assert!(feature.geometry_field(1).is_err());
 }
 
 #[test]
@@ -251,6 +436,18 @@ fn test_create_bbox() {
     assert_eq!(bbox.json().unwrap(), "{ \"type\": \"Polygon\", \"coordinates\": [ [ [ -27.0, 85.0 ], [ 52.0, 85.0 ], [ 52.0, 33.0 ], [ -27.0, 33.0 ], [ -27.0, 85.0 ] ] ] }");
 }
 
+#[test]
+fn test_geometry_wkb() {
+    with_first_feature("roads.geojson", |feature| {
+        let wkb = feature.geometry_wkb().unwrap();
+        // little endian LineString
+        assert_eq!(wkb[0], 1);
+        assert_eq!(wkb[1], 2);
+        let geom = Geometry::from_wkb(&wkb).unwrap();
+        assert!(geom.equals(&feature.geometry().as_geom()));
+    });
+}
+
 #[test]
 fn test_spatial_filter() {
     let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
@@ -263,6 +460,37 @@ fn test_spatial_filter() {
 
     layer.clear_spatial_filter();
     assert_eq!(layer.features().count(), 21);
+
+    let rect = geo_types::Rect::new(
+        geo_types::Coordinate { x: 26.1017, y: 44.4297 },
+        geo_types::Coordinate { x: 26.1025, y: 44.4303 },
+    );
+    layer.set_spatial_filter_geo(rect);
+    assert_eq!(layer.features().count(), 7);
+    layer.clear_spatial_filter();
+}
+
+#[test]
+fn test_set_filters() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let bbox = [26.1017, 44.4297, 26.1025, 44.4303];
+
+    layer.set_filters(Some(bbox), None).unwrap();
+    assert_eq!(layer.features().count(), 7);
+
+    layer.set_filters(Some(bbox), Some("highway = 'footway'")).unwrap();
+    assert_eq!(layer.features().count(), 4);
+
+    // an invalid attribute filter clears both
+    assert!(layer.set_filters(Some(bbox), Some("no_such_field = 1")).is_err());
+    assert_eq!(layer.features().count(), 21);
+
+    layer.set_filters(None, Some("highway = 'footway'")).unwrap();
+    assert_eq!(layer.features().count(), 8);
+
+    layer.clear_filters();
+    assert_eq!(layer.features().count(), 21);
 }
 
 #[test]
@@ -280,6 +508,469 @@ fn test_convex_hull() {
     );
 }
 
+#[test]
+fn test_driver_capability() {
+    let driver = Driver::get(Driver::DRIVER_NAME_GEOJSON).unwrap();
+    assert!(driver.can_create());
+    assert!(driver.test_capability(Driver::CAP_DELETE_DATASOURCE));
+    assert!(!driver.test_capability("NoSuchCapability"));
+}
+
+#[test]
+fn test_driver_all() {
+    let names: Vec<String> = Driver::all().map(|d| d.short_name()).collect();
+    assert!(names.iter().any(|n| n == Driver::DRIVER_NAME_GEOJSON));
+    assert!(names.iter().any(|n| n == Driver::DRIVER_NAME_MEMORY));
+
+    let geojson = Driver::all()
+        .find(|d| d.short_name() == Driver::DRIVER_NAME_GEOJSON)
+        .unwrap();
+    assert!(geojson.can_create());
+}
+
+#[test]
+fn test_driver_delete() {
+    let dir = std::env::temp_dir();
+    let shp_path = dir.join("test_driver_delete.shp");
+    let shp_path = shp_path.to_str().unwrap();
+
+    let driver = Driver::get(Driver::DRIVER_NAME_SHAPEFILE).unwrap();
+    {
+        let ds = driver.create(shp_path).unwrap();
+        let srs = SpatialRef::from_epsg(2056).unwrap();
+        ds.create_layer_ext::<&str>("test_driver_delete", &srs, OGRwkbGeometryType::wkbPolygon, &[])
+            .unwrap();
+    }
+    assert!(Path::new(shp_path).exists());
+    assert!(dir.join("test_driver_delete.dbf").exists());
+
+    driver.delete(shp_path).unwrap();
+    for ext in &["shp", "shx", "dbf", "prj"] {
+        assert!(!dir.join(format!("test_driver_delete.{}", ext)).exists());
+    }
+}
+
+#[test]
+fn test_translate_with_progress() {
+    use super::translate_with_progress;
+
+    let out_path = std::env::temp_dir().join("test_translate_with_progress.gpkg");
+    let out_path = out_path.to_str().unwrap();
+
+    let mut reported = Vec::new();
+    let mut progress = |complete: f64| {
+        reported.push(complete);
+        true
+    };
+    translate_with_progress(fixture!("roads.geojson"), out_path, &["-f", "GPKG"], Some(&mut progress)).unwrap();
+
+    assert!(!reported.is_empty());
+    assert!(reported.iter().all(|c| *c >= 0.0 && *c <= 1.0));
+    assert_eq!(Dataset::open(out_path).unwrap().layer(0).unwrap().count(true), 21);
+    Driver::get(Driver::DRIVER_NAME_GEOPACKAGE).unwrap().delete(out_path).unwrap();
+}
+
+#[test]
+fn test_vector_translate() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let translated = ds
+        .vector_translate("translated", &["-f", "Memory", "-where", "highway = 'footway'", "-t_srs", "EPSG:3857"])
+        .unwrap();
+
+    let layer = translated.layer(0).unwrap();
+    assert_eq!(layer.count(true), 8);
+    // web mercator meters, not degrees
+    assert!(layer.get_extent(true).unwrap().min_x > 2_000_000.0);
+
+    assert!(ds.vector_translate("translated", &["-no_such_option"]).is_err());
+}
+
+#[test]
+fn test_translate_cancelled() {
+    use super::translate_with_progress;
+
+    let out_path = std::env::temp_dir().join("test_translate_cancelled.gpkg");
+    let out_path = out_path.to_str().unwrap();
+
+    let mut calls = 0;
+    let mut cancel = |_complete: f64| {
+        calls += 1;
+        false
+    };
+    assert!(translate_with_progress(fixture!("roads.geojson"), out_path, &["-f", "GPKG"], Some(&mut cancel)).is_err());
+    assert!(calls > 0);
+
+    // whatever was written before the cancellation is a closed, readable dataset
+    if Path::new(out_path).exists() {
+        let ds = Dataset::open(out_path).unwrap();
+        assert!(ds.count() <= 1);
+        drop(ds);
+        Driver::get(Driver::DRIVER_NAME_GEOPACKAGE).unwrap().delete(out_path).unwrap();
+    }
+}
+
+#[test]
+fn test_translate_progress_panics() {
+    use super::translate_with_progress;
+
+    let out_path = std::env::temp_dir().join("test_translate_progress_panics.gpkg");
+    let out_path = out_path.to_str().unwrap();
+
+    // the panic doesn't unwind through GDAL, it cancels the translation
+    let mut progress = |_complete: f64| -> bool { panic!("progress panicked") };
+    assert!(translate_with_progress(fixture!("roads.geojson"), out_path, &["-f", "GPKG"], Some(&mut progress)).is_err());
+    if Path::new(out_path).exists() {
+        Driver::get(Driver::DRIVER_NAME_GEOPACKAGE).unwrap().delete(out_path).unwrap();
+    }
+
+    assert!(super::translate(fixture!("roads.geojson"), out_path, &["-no_such_option"]).is_err());
+}
+
+#[test]
+fn test_open_with_allowed_drivers() {
+    let ds = Dataset::open_with_allowed_drivers(fixture!("roads.geojson"), &["GeoJSON"]).unwrap();
+    assert_eq!(ds.count(), 1);
+    assert!(Dataset::open_with_allowed_drivers(fixture!("roads.geojson"), &["GPKG"]).is_err());
+}
+
+#[test]
+fn test_in_memory_dataset() {
+    let mut ds = Dataset::in_memory().unwrap();
+    assert_eq!(ds.count(), 0);
+    {
+        let layer = ds.create_layer().unwrap();
+        assert_eq!(layer.count(true), 0);
+    }
+    assert_eq!(ds.count(), 1);
+}
+
+#[test]
+fn test_open_streaming() {
+    let ds = Dataset::open_streaming(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    assert_eq!(layer.features().count(), 21);
+
+    // only the GeoJSON drivers are tried
+    assert!(Dataset::open_streaming(fixture!("tinymarble.png")).is_err());
+}
+
+/// Resident set size of this process in kB
+#[cfg(target_os = "linux")]
+fn resident_kb() -> usize {
+    let status = std::fs::read_to_string("/proc/self/status").unwrap();
+    let line = status.lines().find(|l| l.starts_with("VmRSS:")).unwrap();
+    line.split_whitespace().nth(1).unwrap().parse().unwrap()
+}
+
+/// Writes ~1 GB of GeoJSON and checks that reading it through `open_streaming` doesn't hold the
+/// features in memory.  Measures the RSS of the whole process, so run it alone:
+/// `cargo test -p gdal -- --ignored test_open_streaming_bounded_memory`
+#[test]
+#[ignore]
+#[cfg(target_os = "linux")]
+fn test_open_streaming_bounded_memory() {
+    use std::io::Write;
+
+    const FEATURES: usize = 250_000;
+    const VERTICES: usize = 100;
+
+    let path = std::env::temp_dir().join("test_open_streaming_bounded_memory.geojson");
+    {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+        write!(out, "{{\"type\":\"FeatureCollection\",\"features\":[").unwrap();
+        for i in 0..FEATURES {
+            if i > 0 {
+                write!(out, ",").unwrap();
+            }
+            write!(out, "{{\"type\":\"Feature\",\"properties\":{{\"id\":{}}},", i).unwrap();
+            write!(out, "\"geometry\":{{\"type\":\"Polygon\",\"coordinates\":[[").unwrap();
+            for v in 0..=VERTICES {
+                let angle = (v % VERTICES) as f64 / VERTICES as f64 * std::f64::consts::PI * 2.0;
+                let sep = if v > 0 { "," } else { "" };
+                write!(out, "{}[{:.9},{:.9}]", sep, i as f64 + angle.cos(), angle.sin()).unwrap();
+            }
+            write!(out, "]]}}}}").unwrap();
+        }
+        write!(out, "]}}").unwrap();
+    }
+    let file_kb = std::fs::metadata(&path).unwrap().len() as usize / 1024;
+
+    let before = resident_kb();
+    let ds = Dataset::open_streaming(path.to_str().unwrap()).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let mut count = 0;
+    let mut peak = resident_kb();
+    for feature in layer.features() {
+        assert!(feature.has_geometry());
+        count += 1;
+        if count % 1000 == 0 {
+            peak = peak.max(resident_kb());
+        }
+    }
+    drop(ds);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(count, FEATURES);
+    let growth_kb = peak.saturating_sub(before);
+    // GDAL keeps at most ~100 MB of features from its first pass, the rest is read in chunks
+    assert!(
+        growth_kb < 256 * 1024,
+        "reading a {} MB file grew the process by {} MB",
+        file_kb / 1024,
+        growth_kb / 1024
+    );
+}
+
+#[test]
+fn test_create_layer_like() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let template = ds.layer(0).unwrap();
+    let template_fields: Vec<_> = template
+        .layer_definition()
+        .fields()
+        .map(|f| (f.name(), f.field_type()))
+        .collect();
+
+    let out_ds = Dataset::in_memory().unwrap();
+    let layer = out_ds.create_layer_like("aggregated", &template, None).unwrap();
+    assert_eq!(layer.name(), "aggregated");
+    assert_eq!(layer.count(true), 0);
+    let fields: Vec<_> = layer
+        .layer_definition()
+        .fields()
+        .map(|f| (f.name(), f.field_type()))
+        .collect();
+    assert_eq!(fields, template_fields);
+    assert_eq!(
+        layer.spatial_reference().unwrap().auth_code().unwrap(),
+        4326
+    );
+
+    let lv95 = SpatialRef::from_epsg(2056).unwrap();
+    let layer = out_ds.create_layer_like("lv95", &template, Some(&lv95)).unwrap();
+    assert_eq!(layer.spatial_reference().unwrap().auth_code().unwrap(), 2056);
+}
+
+#[test]
+fn test_field_default_and_nullable() {
+    let driver = Driver::get(Driver::DRIVER_NAME_GEOPACKAGE).unwrap();
+    let ds = driver.create("/vsimem/test_field_default.gpkg").unwrap();
+    let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+    let mut layer = ds
+        .create_layer_ext::<String>("buildings", &wgs84, OGRwkbGeometryType::wkbPolygon, &[])
+        .unwrap();
+
+    let aggregated_at = FieldDefinition::new("aggregated_at", OGRFieldType::OFTDateTime).unwrap();
+    aggregated_at.set_default("CURRENT_TIMESTAMP").unwrap();
+    aggregated_at.set_nullable(false);
+    aggregated_at.add_to_layer(&mut layer).unwrap();
+
+    let source = FieldDefinition::new("source", OGRFieldType::OFTString).unwrap();
+    source.add_to_layer(&mut layer).unwrap();
+
+    let defn = layer.layer_definition();
+    let fields: Vec<_> = defn
+        .fields()
+        .map(|f| (f.name(), f.default(), f.is_nullable()))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("aggregated_at".to_string(), Some("CURRENT_TIMESTAMP".to_string()), false),
+            ("source".to_string(), None, true),
+        ]
+    );
+}
+
+#[test]
+fn test_rename_and_alter_field() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let template = ds.layer(0).unwrap();
+    // read only GeoJSON
+    assert!(template.rename_field("highway", "road_class").is_err());
+
+    let out_ds = Dataset::in_memory().unwrap();
+    let layer = out_ds.create_layer_like("roads", &template, None).unwrap();
+    let highway_idx = layer.layer_definition().get_field_index("highway").unwrap();
+
+    layer.rename_field("highway", "road_class").unwrap();
+    let defn = layer.layer_definition();
+    assert!(defn.get_field_index("highway").is_err());
+    assert_eq!(defn.get_field_index("road_class").unwrap(), highway_idx);
+
+    let width = FieldDefinition::new("road_class", OGRFieldType::OFTString).unwrap();
+    width.set_width(12);
+    layer.alter_field("road_class", &width, ALTER_WIDTH_PRECISION_FLAG).unwrap();
+    assert_eq!(defn.get_field(highway_idx).width(), 12);
+
+    assert!(layer.rename_field("no_such_field", "x").is_err());
+}
+
+#[test]
+fn test_delete_field() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let template = ds.layer(0).unwrap();
+    assert!(template.delete_field("highway").is_err());
+
+    let out_ds = Dataset::in_memory().unwrap();
+    let layer = out_ds.create_layer_like("roads", &template, None).unwrap();
+    let field_count = layer.layer_definition().fields().count();
+
+    layer.delete_field("highway").unwrap();
+    let defn = layer.layer_definition();
+    assert_eq!(defn.fields().count(), field_count - 1);
+    assert!(defn.get_field_index("highway").is_err());
+    assert!(layer.delete_field("highway").is_err());
+}
+
+#[test]
+fn test_field_stats() {
+    let ds = Dataset::open(fixture!("roads.geojson")).unwrap();
+    let layer = ds.layer(0).unwrap();
+    let stats = layer.field_stats("sort_key").unwrap();
+    assert_eq!(stats.count + stats.null_count, 21);
+    let (min, max, mean) = (stats.min.unwrap(), stats.max.unwrap(), stats.mean.unwrap());
+    assert!(min <= mean && mean <= max);
+
+    let err = layer.field_stats("highway").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<crate::errors::ErrorKind>(),
+        Some(crate::errors::ErrorKind::NonNumericField { .. })
+    ));
+    assert!(layer.field_stats("no_such_field").is_err());
+}
+
+#[test]
+fn test_delete_features_where() {
+    let mut ds = Dataset::in_memory().unwrap();
+    let mut layer = ds.create_layer().unwrap();
+    layer.create_defn_fields(&[("height", OGRFieldType::OFTReal)]).unwrap();
+    for height in [0.0, -1.0, 12.5, 30.0].iter() {
+        layer
+            .create_feature_fields(
+                Geometry::from_wkt("POINT (1 2)").unwrap(),
+                &["height"],
+                &[FieldValue::RealValue(*height)],
+            )
+            .unwrap();
+    }
+
+    assert_eq!(layer.delete_features_where("height <= 0").unwrap(), 2);
+    assert_eq!(layer.count(true), 2);
+    assert_eq!(layer.delete_features_where("height > 100").unwrap(), 0);
+    assert_eq!(layer.count(true), 2);
+}
+
+#[test]
+fn test_set_geometry() {
+    let mut ds = Dataset::in_memory().unwrap();
+    let mut layer = ds.create_layer().unwrap();
+    layer.create_feature(Geometry::from_wkt("POINT (1 2)").unwrap()).unwrap();
+
+    let union = Geometry::from_wkt("POINT (3 4)").unwrap();
+    {
+        let mut feature = layer.get_feature_by_id(0).unwrap();
+        feature.set_geometry(&union).unwrap();
+        layer.set_feature(&feature).unwrap();
+    }
+    // the feature got a copy
+    assert_eq!(union.wkt().unwrap(), "POINT (3 4)");
+
+    let feature = layer.get_feature_by_id(0).unwrap();
+    assert_eq!(feature.geometry().as_geom().wkt().unwrap(), "POINT (3 4)");
+}
+
+#[test]
+fn test_total_area() {
+    let lv95 = SpatialRef::from_epsg(2056).unwrap();
+    let ds = Dataset::in_memory().unwrap();
+    let mut layer = ds
+        .create_layer_ext::<&str>("buildings", &lv95, OGRwkbGeometryType::wkbUnknown, &[])
+        .unwrap();
+    layer
+        .create_feature(Geometry::from_wkt("POLYGON ((2600000 1200000,2600100 1200000,2600100 1200100,2600000 1200100,2600000 1200000))").unwrap())
+        .unwrap();
+    layer
+        .create_feature(Geometry::from_wkt("LINESTRING (2600000 1200000,2600100 1200100)").unwrap())
+        .unwrap();
+
+    let area = layer.total_area(&lv95).unwrap();
+    assert!((area - 10000.0).abs() < 1e-6);
+
+    let lv03 = SpatialRef::from_epsg(21781).unwrap();
+    let area = layer.total_area(&lv03).unwrap();
+    assert!((area - 10000.0).abs() < 1.0);
+}
+
+#[test]
+fn test_normalize_axis_order() {
+    // what a read of EPSG:4326 in the authority order gives: latitude, longitude
+    let wgs84 = SpatialRef::from_epsga(4326).unwrap();
+    let ds = Dataset::in_memory().unwrap();
+    let mut layer = ds
+        .create_layer_ext::<&str>("bern", &wgs84, OGRwkbGeometryType::wkbPoint, &[])
+        .unwrap();
+    layer
+        .create_feature(Geometry::from_wkt("POINT (46.9511 7.4386)").unwrap())
+        .unwrap();
+    assert!(layer.spatial_reference().unwrap().is_lat_long_order());
+
+    let points: Vec<[f64; 2]> = layer
+        .normalize_axis_order()
+        .unwrap()
+        .map(|f| f.geometry().as_geom().get_point(0))
+        .collect();
+    assert_eq!(points, vec![[7.4386, 46.9511]]);
+
+    // the dataset is left untouched, a second pass gives the same coordinates
+    let feature = layer.get_feature_by_id(0).unwrap();
+    assert_eq!(feature.geometry().as_geom().get_point(0), [46.9511, 7.4386]);
+    let feature = layer.normalize_axis_order().unwrap().next().unwrap();
+    assert_eq!(feature.geometry().as_geom().get_point(0), [7.4386, 46.9511]);
+
+    // nothing to swap in the traditional GIS order
+    let lv95 = SpatialRef::from_epsg(2056).unwrap();
+    let mut layer = ds
+        .create_layer_ext::<&str>("bern_lv95", &lv95, OGRwkbGeometryType::wkbPoint, &[])
+        .unwrap();
+    layer
+        .create_feature(Geometry::from_wkt("POINT (2600000 1200000)").unwrap())
+        .unwrap();
+    let feature = layer.normalize_axis_order().unwrap().next().unwrap();
+    assert_eq!(feature.geometry().as_geom().get_point(0), [2600000.0, 1200000.0]);
+}
+
+#[test]
+fn test_layer_metadata() {
+    use crate::metadata::Metadata;
+
+    let mut ds = Dataset::in_memory().unwrap();
+    let mut layer = ds.create_layer().unwrap();
+    assert!(layer.metadata_item("CELL_SIZE", "").is_none());
+    layer.set_metadata_item("CELL_SIZE", "100", "").unwrap();
+    layer.set_metadata_item("SOURCE", "roads.geojson", "AGGREGATION").unwrap();
+    assert_eq!(layer.metadata_item("CELL_SIZE", ""), Some("100".to_string()));
+    assert_eq!(
+        layer.metadata_item("SOURCE", "AGGREGATION"),
+        Some("roads.geojson".to_string())
+    );
+}
+
+#[test]
+fn test_dataset_metadata() {
+    use crate::metadata::Metadata;
+
+    let mut ds = Dataset::in_memory().unwrap();
+    ds.set_metadata_item("PROCESSING_SOFTWARE", "bldg_agg", "").unwrap();
+    ds.set_metadata_item("SOURCE_CRS", "EPSG:2056", "PROVENANCE").unwrap();
+    assert_eq!(
+        ds.metadata_item("PROCESSING_SOFTWARE", ""),
+        Some("bldg_agg".to_string())
+    );
+    let domains = ds.metadata_domains();
+    assert!(domains.contains(&"PROVENANCE".to_string()));
+}
+
 #[test]
 fn test_write_features() {
     use std::fs;
@@ -306,7 +997,8 @@ fn test_write_features() {
                 ],
             )
             .unwrap();
-        // dataset is closed here
+        drop(layer);
+        ds.close().unwrap();
     }
 
     let ds = Dataset::open(fixture!("output.geojson")).unwrap();
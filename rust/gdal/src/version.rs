@@ -19,6 +19,9 @@ use crate::utils::_string;
 use gdal_sys;
 use std::ffi::CString;
 
+/// Runtime GDAL version, `key` is one of "VERSION_NUM", "RELEASE_DATE", "RELEASE_NAME",
+/// "--version", "BUILD_INFO" or "LICENSE".  VERSION_NUM is e.g. "3030100" for 3.3.1,
+/// useful to adapt at runtime to behavior differences the gdal_3_x features can't see
 pub fn version_info(key: &str) -> String {
     let c_key = CString::new(key.as_bytes()).unwrap();
     _string(unsafe { gdal_sys::GDALVersionInfo(c_key.as_ptr()) })
@@ -46,4 +49,12 @@ mod tests {
 
         assert_eq!(version_text, expected_text);
     }
+
+    #[test]
+    fn test_version_num() {
+        let version_num: u32 = version_info("VERSION_NUM").parse().unwrap();
+        let release_name = version_info("RELEASE_NAME");
+        let major: u32 = release_name.split('.').next().unwrap().parse().unwrap();
+        assert_eq!(version_num / 1_000_000, major);
+    }
 }
@@ -183,3 +183,25 @@ fn test_import_export_geometrycollection() {
     );
     assert_eq!(geo.to_gdal().unwrap().wkt().unwrap(), wkt);
 }
+
+#[test]
+fn test_srid_round_trip() {
+    use gdal::spatial_ref::SpatialRef;
+    use geos::SimpleContextHandle;
+    use crate::convert::{convert_from_gdal_to_geos_with_srid, convert_geos_to_gdal_with_srid};
+
+    let wkt = "POLYGON ((2600000 1200000,2600100 1200000,2600100 1200100,2600000 1200000))";
+    let mut gdal_geom = GdalGeometry::from_wkt(wkt).unwrap();
+    gdal_geom.set_spatial_reference(&SpatialRef::from_epsg(2056).unwrap());
+
+    let context = SimpleContextHandle::new();
+    let geos_geom = convert_from_gdal_to_geos_with_srid(&gdal_geom, &context, false, Some(2056)).unwrap();
+    assert_eq!(geos_geom.srid(), 2056);
+
+    // no SRID given, the one of the GEOS geometry is used
+    let round_trip = convert_geos_to_gdal_with_srid(&geos_geom, None).unwrap();
+    assert_eq!(round_trip.wkt().unwrap(), wkt);
+    let srs = round_trip.spatial_reference().unwrap();
+    assert_eq!(srs.auth_code().unwrap(), 2056);
+    assert_eq!(srs, SpatialRef::from_epsg(2056).unwrap());
+}
@@ -91,6 +91,16 @@ pub fn convert_from_gdal_to_geos<'c>(
     })
 }
 
+/// Same as convert_from_gdal_to_geos, stamping the result with the given SRID (EPSG code)
+/// so that EWKB written from the GEOS geometry carries the CRS
+pub fn convert_from_gdal_to_geos_with_srid<'c>(
+    geo: &GdalGeometry, context: &'c SimpleContextHandle, create_rings: bool, srid: Option<i32>) -> Result<SimpleGeometry<'c>> {
+    let geos_geom = convert_from_gdal_to_geos(geo, context, create_rings)?;
+    if let Some(srid) = srid {
+        geos_geom.set_srid(srid);
+    }
+    Ok(geos_geom)
+}
 
 pub fn convert_from_gdal_to_geos_no_holes<'c>(
     geo: &GdalGeometry, context: &'c SimpleContextHandle, create_rings: bool) -> Result<SimpleGeometry<'c>> {
@@ -18,6 +18,7 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 use anyhow::{Result, bail};
 use geos::{SimpleGeometry, SimpleCoordinateSequence, GeometryTypes};
 use gdal::vector::{OGRwkbGeometryType, Geometry as GdalGeometry};
+use gdal::spatial_ref::SpatialRef;
 
 fn geometry_with_points(
     wkb_type: OGRwkbGeometryType::Type,
@@ -60,6 +61,19 @@ pub fn convert_geos_to_gdal(geom: &SimpleGeometry) -> Result<GdalGeometry> {
     }
 }
 
+/// Same as convert_geos_to_gdal, setting the spatial reference from the given EPSG code,
+/// or from the SRID of the GEOS geometry when none is given and it has one
+pub fn convert_geos_to_gdal_with_srid(geom: &SimpleGeometry, srid: Option<i32>) -> Result<GdalGeometry> {
+    let mut gdal_geom = convert_geos_to_gdal(geom)?;
+
+    let srid = srid.unwrap_or_else(|| geom.srid());
+    if srid > 0 {
+        let srs = SpatialRef::from_epsg(srid as u32)?;
+        gdal_geom.set_spatial_reference(&srs);
+    }
+    Ok(gdal_geom)
+}
+
 pub fn convert_geos_to_gdal_no_holes(geom: &SimpleGeometry) -> Result<GdalGeometry> {
     match geom.geometry_type() {
         GeometryTypes::Point => bail!("not implemented yet"),
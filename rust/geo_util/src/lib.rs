@@ -28,3 +28,5 @@ pub mod io;
 pub mod pq;
 
 pub mod vector;
+
+pub use util::geos_version;
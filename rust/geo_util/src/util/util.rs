@@ -16,6 +16,7 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 use std::time::{Duration, Instant};
+use anyhow::{bail, Result};
 
 pub fn format_duration(d: Duration) -> String {
     let mut secs = d.as_secs();
@@ -96,6 +97,25 @@ pub fn print_remaining_time_msg(now: &Instant, num_processed: u32, num_total: u3
 
 
 
+/// Runtime GEOS version as (major, minor, patch), to check for functions newer GEOS versions bring
+pub fn geos_version() -> Result<(u32, u32, u32)> {
+    parse_geos_version(&geos::version()?)
+}
+
+/// Parses a GEOSversion string such as "3.8.1-CAPI-1.13.3" or "3.11.0beta1-CAPI-1.17.0"
+fn parse_geos_version(version: &str) -> Result<(u32, u32, u32)> {
+    let release = version.split('-').next().unwrap_or_default();
+    let mut numbers = release.split('.').map(|part| {
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse::<u32>()
+    });
+
+    match (numbers.next(), numbers.next(), numbers.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch))) => Ok((major, minor, patch)),
+        _ => bail!("Unable to parse GEOS version {}", version),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,5 +126,12 @@ mod tests {
 
         assert_eq!(quote_csv_string("hel\\l\"o"), "\"hel\\\\l\\\"o\"");
     }
+
+    #[test]
+    fn test_parse_geos_version() {
+        assert_eq!(parse_geos_version("3.8.1-CAPI-1.13.3").unwrap(), (3, 8, 1));
+        assert_eq!(parse_geos_version("3.11.0beta1-CAPI-1.17.0").unwrap(), (3, 11, 0));
+        assert!(parse_geos_version("not a version").is_err());
+    }
 }
 
@@ -0,0 +1,511 @@
+/*
+This file is part of the Building Aggregration Tool
+Copyright (C) 2022 Novel-T
+
+The Building Aggregration Tool is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+
+use anyhow::{anyhow, bail, Result};
+use gdal::vector::{Layer, ProgressCallback};
+use geos::{GeometryTypes, PreparedGeometry, SimpleContextHandle, SimpleGeometry, WKBReader, WKBWriter};
+
+/// Unions `geoms` on up to `threads` worker threads, the result belongs to `context`.
+///
+/// The geometries are split into spatially coherent partitions (sort-tile-recursive, like the
+/// GEOS STRtree, see `str_partition`), each partition is unioned on its own thread and the partial results are
+/// unioned at the end.
+///
+/// A GEOS context handle is not thread safe, so a `SimpleContextHandle` and every geometry
+/// created with it have to stay on one thread.  Geometries cross threads as WKB and each
+/// worker creates its own context.
+pub fn parallel_union<'c>(
+    context: &'c SimpleContextHandle,
+    geoms: Vec<SimpleGeometry<'c>>,
+    threads: usize,
+) -> Result<SimpleGeometry<'c>> {
+    parallel_union_with_progress(context, geoms, threads, None)
+}
+
+/// Same as parallel_union, reporting progress to `progress` as the partitions complete.  When it
+/// cancels, an error is returned without waiting for the partitions still running; their threads
+/// finish in the background and their results are discarded
+pub fn parallel_union_with_progress<'c>(
+    context: &'c SimpleContextHandle,
+    geoms: Vec<SimpleGeometry<'c>>,
+    threads: usize,
+    mut progress: Option<&mut ProgressCallback>,
+) -> Result<SimpleGeometry<'c>> {
+    if geoms.is_empty() {
+        return SimpleGeometry::create_empty_collection(context, GeometryTypes::GeometryCollection);
+    }
+    let threads = threads.max(1).min(geoms.len());
+
+    let writer = WKBWriter::new(context)?;
+    let mut centers = Vec::with_capacity(geoms.len());
+    let mut wkbs = Vec::with_capacity(geoms.len());
+    for geom in geoms.iter() {
+        centers.push(geom.center()?);
+        wkbs.push(Some(writer.write_wkb(geom)?.as_ref().to_vec()));
+    }
+    drop(geoms);
+
+    let handles: Vec<_> = str_partition(&centers, threads)
+        .into_iter()
+        .map(|part| {
+            let part_wkbs: Vec<Vec<u8>> = part.iter().map(|&i| wkbs[i].take().unwrap()).collect();
+            thread::spawn(move || union_wkbs(&part_wkbs))
+        })
+        .collect();
+
+    // the final union of the partial results counts as one more step
+    let n_steps = (handles.len() + 1) as f64;
+    let mut partial_wkbs = Vec::with_capacity(handles.len());
+    for handle in handles {
+        let wkb = handle.join().map_err(|_| anyhow!("Union worker thread panicked"))??;
+        partial_wkbs.push(wkb);
+        if let Some(progress) = progress.as_mut() {
+            if !progress(partial_wkbs.len() as f64 / n_steps) {
+                bail!("parallel_union cancelled");
+            }
+        }
+    }
+
+    let reader = WKBReader::new(context)?;
+    let partial_geoms = partial_wkbs
+        .iter()
+        .map(|wkb| reader.read_wkb(wkb))
+        .collect::<Result<Vec<_>>>()?;
+
+    let union = SimpleGeometry::create_multi_geom(context, partial_geoms, GeometryTypes::GeometryCollection)?
+        .union_unary(context)?;
+    if let Some(progress) = progress.as_mut() {
+        progress(1.0);
+    }
+    Ok(union)
+}
+
+/// Unions polygons that already tile the area, like building blocks or administrative units,
+/// with the GEOS coverage union, which only has to drop the shared edges and is far faster than
+/// `union_unary` or `parallel_union`.
+///
+/// The polygons must form a valid coverage: no overlaps and no gaps along shared borders where
+/// the vertices differ.  GEOS does not check this, an invalid coverage gives an undefined result;
+/// check untrusted data with `coverage_is_valid` first.
+pub fn coverage_union<'c>(
+    context: &'c SimpleContextHandle,
+    geoms: Vec<SimpleGeometry<'c>>,
+) -> Result<SimpleGeometry<'c>> {
+    if geoms.is_empty() {
+        return SimpleGeometry::create_empty_collection(context, GeometryTypes::GeometryCollection);
+    }
+    SimpleGeometry::create_multi_geom(context, geoms, GeometryTypes::GeometryCollection)?
+        .coverage_union(context)
+}
+
+/// Whether `geoms` form a valid polygonal coverage, the precondition of `coverage_union`: no
+/// overlaps and no gaps narrower than `gap_width` between neighbouring polygons.  Wider gaps
+/// are taken as real holes in the coverage.
+#[cfg(feature = "geos_3_12")]
+pub fn coverage_is_valid<'c>(
+    context: &'c SimpleContextHandle,
+    geoms: &[SimpleGeometry<'c>],
+    gap_width: f64,
+) -> Result<bool> {
+    let copies = geoms.iter().map(|g| g.clone(context)).collect::<Result<Vec<_>>>()?;
+    SimpleGeometry::create_multi_geom(context, copies, GeometryTypes::GeometryCollection)?
+        .coverage_is_valid(gap_width)
+}
+
+/// Unary union of WKB geometries with a context owned by the calling thread, returned as WKB
+fn union_wkbs(wkbs: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let context = SimpleContextHandle::new();
+    let reader = WKBReader::new(&context)?;
+    let writer = WKBWriter::new(&context)?;
+
+    let geoms = wkbs.iter().map(|wkb| reader.read_wkb(wkb)).collect::<Result<Vec<_>>>()?;
+    let union = SimpleGeometry::create_multi_geom(&context, geoms, GeometryTypes::GeometryCollection)?
+        .union_unary(&context)?;
+
+    let wkb = writer.write_wkb(&union)?.as_ref().to_vec();
+    Ok(wkb)
+}
+
+/// Totals of one group of `summarize_by`
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupSummary {
+    /// Value of the group field, `None` for features where it is unset or null
+    pub key: Option<String>,
+    pub count: u64,
+    /// One sum per requested field, in the same order
+    pub sums: Vec<f64>,
+}
+
+/// Attribute side of a dissolve: groups the features of `layer` (respecting its filters) by the
+/// text value of `group_field` and sums each of the numeric `sum_fields`, in one pass.
+///
+/// Groups are returned in the order they are first met.  Null values are left out of the sums
+/// but the feature still counts towards `count`.
+pub fn summarize_by(layer: &Layer, group_field: &str, sum_fields: &[&str]) -> Result<Vec<GroupSummary>> {
+    summarize_by_with_progress(layer, group_field, sum_fields, None)
+}
+
+/// Same as summarize_by, reporting the fraction of the layer's features read to `progress`.
+/// When it cancels, an error is returned and the partial sums are discarded
+pub fn summarize_by_with_progress(
+    layer: &Layer,
+    group_field: &str,
+    sum_fields: &[&str],
+    mut progress: Option<&mut ProgressCallback>,
+) -> Result<Vec<GroupSummary>> {
+    let layer_defn = layer.layer_definition();
+    let group_idx = layer_defn.get_field_index(group_field)?;
+
+    let sum_idxs = sum_fields
+        .iter()
+        .map(|field| layer_defn.get_numeric_field_index(field))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut summaries: Vec<GroupSummary> = Vec::new();
+    let mut group_positions: HashMap<Option<String>, usize> = HashMap::new();
+    let total = if progress.is_some() { layer.count(true).max(1) as f64 } else { 1.0 };
+
+    for (n_read, feature) in layer.features().enumerate() {
+        if let Some(progress) = progress.as_mut() {
+            if !progress(n_read as f64 / total) {
+                bail!("summarize_by cancelled");
+            }
+        }
+
+        let key = feature.field_as_string(group_idx);
+        let pos = *group_positions.entry(key.clone()).or_insert_with(|| {
+            summaries.push(GroupSummary { key, count: 0, sums: vec![0.0; sum_idxs.len()] });
+            summaries.len() - 1
+        });
+
+        let summary = &mut summaries[pos];
+        summary.count += 1;
+        for (sum, &field_idx) in summary.sums.iter_mut().zip(sum_idxs.iter()) {
+            if feature.is_field_set_and_not_null(field_idx) {
+                *sum += feature.get_field_as_real(field_idx);
+            }
+        }
+    }
+
+    if let Some(progress) = progress.as_mut() {
+        progress(1.0);
+    }
+    Ok(summaries)
+}
+
+/// Whether `query` intersects at least one of `targets`.  `query` is prepared once for all the
+/// tests and the loop stops at the first hit, the inner predicate of clustering and spatial joins.
+pub fn any_intersects(query: &SimpleGeometry, targets: &[SimpleGeometry]) -> Result<bool> {
+    if targets.is_empty() {
+        return Ok(false);
+    }
+    let prepared = PreparedGeometry::new(query)?;
+    for target in targets {
+        if prepared.intersects(target)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Which neighbours of a grid cell count as adjacent in `grow_regions`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Connectivity {
+    /// Cells sharing an edge
+    Four,
+    /// Cells sharing an edge or a corner
+    Eight,
+}
+
+/// Groups the cells whose value exceeds `threshold` into contiguous regions (flood fill).
+///
+/// Cells are keyed by their (column, row) grid index, e.g. as produced by a density grid.
+/// Each region is sorted, and regions are ordered by their smallest cell, so the output is
+/// deterministic whatever the iteration order of `cells`.
+pub fn grow_regions(
+    cells: &HashMap<(i64, i64), f64>,
+    threshold: f64,
+    connectivity: Connectivity,
+) -> Vec<Vec<(i64, i64)>> {
+    let neighbours: &[(i64, i64)] = match connectivity {
+        Connectivity::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+        Connectivity::Eight => &[
+            (1, 0), (-1, 0), (0, 1), (0, -1),
+            (1, 1), (1, -1), (-1, 1), (-1, -1),
+        ],
+    };
+
+    let mut seeds: Vec<(i64, i64)> = cells
+        .iter()
+        .filter(|(_, &value)| value > threshold)
+        .map(|(&cell, _)| cell)
+        .collect();
+    seeds.sort();
+
+    let mut visited: HashSet<(i64, i64)> = HashSet::with_capacity(seeds.len());
+    let mut regions = Vec::new();
+
+    for seed in seeds {
+        if !visited.insert(seed) {
+            continue;
+        }
+
+        let mut region = Vec::new();
+        let mut stack = vec![seed];
+        while let Some((col, row)) = stack.pop() {
+            region.push((col, row));
+            for (d_col, d_row) in neighbours {
+                let next = (col + d_col, row + d_row);
+                let above = cells.get(&next).map_or(false, |&value| value > threshold);
+                if above && visited.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        region.sort();
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// Sort-tile-recursive split of the indexes of `centers` into at most `n_parts` groups:
+/// vertical slices by x, then each slice cut by y.
+///
+/// This is the packing the GEOS STRtree does to build its nodes, but the C API doesn't expose
+/// the nodes of a tree, only queries on it, so the partitions can't be read from a GEOSSTRtree
+fn str_partition(centers: &[(f64, f64)], n_parts: usize) -> Vec<Vec<usize>> {
+    let n_slices = (n_parts as f64).sqrt().ceil() as usize;
+    let n_per_part = (centers.len() + n_parts - 1) / n_parts;
+    let n_per_slice = n_per_part * ((n_parts + n_slices - 1) / n_slices);
+
+    let mut indexes: Vec<usize> = (0..centers.len()).collect();
+    indexes.sort_by(|&a, &b| centers[a].0.partial_cmp(&centers[b].0).unwrap_or(Ordering::Equal));
+
+    let mut parts = Vec::with_capacity(n_parts);
+    for slice in indexes.chunks_mut(n_per_slice) {
+        slice.sort_by(|&a, &b| centers[a].1.partial_cmp(&centers[b].1).unwrap_or(Ordering::Equal));
+        for part in slice.chunks(n_per_part) {
+            parts.push(part.to_vec());
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_str_partition() {
+        let centers: Vec<(f64, f64)> = (0..10)
+            .flat_map(|x| (0..10).map(move |y| (x as f64, y as f64)))
+            .collect();
+
+        let parts = str_partition(&centers, 4);
+        assert_eq!(parts.len(), 4);
+        assert!(parts.iter().all(|p| p.len() == 25));
+
+        let parts = str_partition(&centers, 3);
+        assert!(parts.len() <= 3);
+        assert_eq!(parts.iter().map(|p| p.len()).sum::<usize>(), 100);
+
+        // more partitions than centers, one center per partition
+        let parts = str_partition(&centers[..3], 10);
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.len() == 1));
+    }
+
+    #[test]
+    fn test_summarize_by() {
+        use gdal::vector::{Dataset, FieldValue, Geometry, OGRFieldType};
+
+        let mut ds = Dataset::in_memory().unwrap();
+        let mut layer = ds.create_layer().unwrap();
+        layer.create_defn_fields(&[
+            ("kind", OGRFieldType::OFTString),
+            ("floors", OGRFieldType::OFTInteger),
+            ("area", OGRFieldType::OFTReal),
+        ]).unwrap();
+
+        for &(kind, floors, area) in &[("house", 2, 80.5), ("shop", 1, 200.0), ("house", 3, 120.0)] {
+            layer.create_feature_fields(
+                Geometry::from_wkt("POINT (0 0)").unwrap(),
+                &["kind", "floors", "area"],
+                &[
+                    FieldValue::StringValue(kind.to_string()),
+                    FieldValue::IntegerValue(floors),
+                    FieldValue::RealValue(area),
+                ],
+            ).unwrap();
+        }
+        // no kind and no area
+        layer.create_feature_fields(
+            Geometry::from_wkt("POINT (0 0)").unwrap(),
+            &["floors"],
+            &[FieldValue::IntegerValue(4)],
+        ).unwrap();
+
+        let summaries = summarize_by(&layer, "kind", &["floors", "area"]).unwrap();
+        assert_eq!(summaries, vec![
+            GroupSummary { key: Some("house".to_string()), count: 2, sums: vec![5.0, 200.5] },
+            GroupSummary { key: Some("shop".to_string()), count: 1, sums: vec![1.0, 200.0] },
+            GroupSummary { key: None, count: 1, sums: vec![4.0, 0.0] },
+        ]);
+
+        assert!(summarize_by(&layer, "kind", &["kind"]).is_err());
+        assert!(summarize_by(&layer, "no such field", &["area"]).is_err());
+
+        let mut reported = Vec::new();
+        let mut record = |complete: f64| {
+            reported.push(complete);
+            true
+        };
+        summarize_by_with_progress(&layer, "kind", &["area"], Some(&mut record)).unwrap();
+        assert_eq!(reported, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+
+        // cancel after the second feature
+        let mut calls = 0;
+        let mut cancel = |_complete: f64| {
+            calls += 1;
+            calls < 2
+        };
+        assert!(summarize_by_with_progress(&layer, "kind", &["area"], Some(&mut cancel)).is_err());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_any_intersects() {
+        let context = SimpleContextHandle::new();
+        let query = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap();
+
+        let far = SimpleGeometry::create_rectangle(&context, 5.0, 5.0, 6.0, 6.0).unwrap();
+        let touching = SimpleGeometry::create_rectangle(&context, 1.0, 0.0, 2.0, 1.0).unwrap();
+
+        assert!(!any_intersects(&query, &[]).unwrap());
+        assert!(!any_intersects(&query, &[far.clone_geom().unwrap()]).unwrap());
+        assert!(any_intersects(&query, &[far, touching]).unwrap());
+    }
+
+    #[test]
+    fn test_grow_regions() {
+        let mut cells = HashMap::new();
+        // an L shaped block, a cell touching it only by a corner and a cell under the threshold
+        for &cell in &[(0, 0), (1, 0), (0, 1), (0, 2)] {
+            cells.insert(cell, 5.0);
+        }
+        cells.insert((1, 3), 4.0);
+        cells.insert((5, 5), 1.0);
+        cells.insert((2, 0), 2.0);
+
+        let regions = grow_regions(&cells, 2.0, Connectivity::Four);
+        assert_eq!(regions, vec![vec![(0, 0), (0, 1), (0, 2), (1, 0)], vec![(1, 3)]]);
+
+        let regions = grow_regions(&cells, 2.0, Connectivity::Eight);
+        assert_eq!(regions, vec![vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 3)]]);
+
+        assert!(grow_regions(&cells, 10.0, Connectivity::Eight).is_empty());
+    }
+
+    /// 2x2 unit squares sharing their edges
+    fn tiles(context: &SimpleContextHandle) -> Vec<SimpleGeometry> {
+        vec![
+            SimpleGeometry::create_rectangle(context, 0.0, 0.0, 1.0, 1.0).unwrap(),
+            SimpleGeometry::create_rectangle(context, 1.0, 0.0, 2.0, 1.0).unwrap(),
+            SimpleGeometry::create_rectangle(context, 0.0, 1.0, 1.0, 2.0).unwrap(),
+            SimpleGeometry::create_rectangle(context, 1.0, 1.0, 2.0, 2.0).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_coverage_union() {
+        let context = SimpleContextHandle::new();
+
+        let union = coverage_union(&context, tiles(&context)).unwrap();
+        assert!((union.area().unwrap() - 4.0).abs() < 1e-9);
+        assert_eq!(union.get_num_geometries().unwrap(), 1);
+
+        let empty = coverage_union(&context, Vec::new()).unwrap();
+        assert!(empty.is_empty().unwrap());
+    }
+
+    #[cfg(feature = "geos_3_12")]
+    #[test]
+    fn test_coverage_is_valid() {
+        let context = SimpleContextHandle::new();
+
+        let mut geoms = tiles(&context);
+        assert!(coverage_is_valid(&context, &geoms, 0.0).unwrap());
+
+        // overlaps its right neighbour
+        geoms[0] = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.5, 1.0).unwrap();
+        assert!(!coverage_is_valid(&context, &geoms, 0.0).unwrap());
+
+        // a 0.1 wide gap, only reported when narrower than gap_width
+        let geoms = vec![
+            SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap(),
+            SimpleGeometry::create_rectangle(&context, 1.1, 0.0, 2.0, 1.0).unwrap(),
+        ];
+        assert!(coverage_is_valid(&context, &geoms, 0.0).unwrap());
+        assert!(!coverage_is_valid(&context, &geoms, 0.5).unwrap());
+    }
+
+    #[test]
+    fn test_parallel_union() {
+        let context = SimpleContextHandle::new();
+
+        // overlapping unit squares shifted by 0.5 along a diagonal band
+        fn squares(context: &SimpleContextHandle) -> Vec<SimpleGeometry> {
+            (0..40)
+                .map(|i| {
+                    let d = i as f64 * 0.5;
+                    SimpleGeometry::create_rectangle(context, d, d, d + 1.0, d + 1.0).unwrap()
+                })
+                .collect()
+        }
+
+        let expected = SimpleGeometry::create_multi_geom(
+            &context,
+            squares(&context),
+            GeometryTypes::MultiPolygon,
+        )
+        .unwrap()
+        .union_unary(&context)
+        .unwrap();
+
+        let union = parallel_union(&context, squares(&context), 4).unwrap();
+
+        assert!((union.area().unwrap() - expected.area().unwrap()).abs() < 1e-9);
+        assert_eq!(union.get_num_geometries().unwrap(), 1);
+
+        let mut reported = Vec::new();
+        let mut record = |complete: f64| {
+            reported.push(complete);
+            true
+        };
+        parallel_union_with_progress(&context, squares(&context), 4, Some(&mut record)).unwrap();
+        assert_eq!(reported, vec![0.2, 0.4, 0.6, 0.8, 1.0]);
+
+        let mut cancel = |_complete: f64| false;
+        assert!(parallel_union_with_progress(&context, squares(&context), 4, Some(&mut cancel)).is_err());
+    }
+}
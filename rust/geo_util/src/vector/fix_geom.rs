@@ -132,7 +132,7 @@ fn fix_geometry(
         let input_fid = input_feature.fid();
 
         let geom = input_feature.geometry().as_geom();
-        let geom = get_fixed_geom(geom);
+        let geom = get_fixed_geom(geom)?;
 
         assert!(geom.is_valid());
 
@@ -167,15 +167,15 @@ fn fix_geometry(
 
 }
 
-// Returns a MultiPolygon with the fixed geometry
-pub fn get_fixed_geom(mut geom: GdalGeometry) -> GdalGeometry {
+// Returns a MultiPolygon with the fixed geometry, errors if a curve can't be linearized
+pub fn get_fixed_geom(mut geom: GdalGeometry) -> Result<GdalGeometry> {
 
 
     // First make sure we don't have any curves
     let has_curve_geometry = geom.has_curve_geometry(false);
     if has_curve_geometry {
         //debug!("Converting curved to linear for {}", input_fid);
-        geom = geom.get_linear_geometry();
+        geom = geom.get_linear_geometry(0.0)?;
     }
 
     debug_assert!(!geom.has_curve_geometry(true));
@@ -260,5 +260,5 @@ pub fn get_fixed_geom(mut geom: GdalGeometry) -> GdalGeometry {
 
     assert!(geom.is_owned());
 
-    return geom;
+    Ok(geom)
 }
\ No newline at end of file
@@ -19,8 +19,12 @@ mod fix_geom;
 mod transform;
 mod area;
 mod columns;
+mod aggregate;
+mod simplify;
 
 pub use fix_geom::*;
 pub use transform::*;
 pub use area::*;
-pub use columns::*;
\ No newline at end of file
+pub use columns::*;
+pub use aggregate::*;
+pub use simplify::*;
\ No newline at end of file
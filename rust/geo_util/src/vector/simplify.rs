@@ -0,0 +1,88 @@
+/*
+This file is part of the Building Aggregration Tool
+Copyright (C) 2022 Novel-T
+
+The Building Aggregration Tool is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+use anyhow::{bail, Result};
+use geo::algorithm::simplifyvw::SimplifyVWPreserve;
+use geo::Geometry;
+use geos::{GeometryTypes, SimpleContextHandle, SimpleGeometry};
+
+use crate::convert::{convert_from_gdal_to_geos, convert_geos_to_gdal, ToGdal, ToRustGeo};
+
+/// Visvalingam-Whyatt simplification of a polygon or multipolygon: repeatedly drops the vertex
+/// forming the smallest triangle with its neighbours, until every remaining triangle is at least
+/// `area_tolerance` (in squared units of the coordinates).
+///
+/// Unlike the Douglas-Peucker `SimpleGeometry::simplify`, narrow spikes and notches are removed
+/// whatever their length, which suits the generalization of building outlines and aggregated
+/// blocks.  The topology preserving variant is used, so rings don't become self-intersecting.
+///
+/// GEOS doesn't expose this algorithm; the geometry goes through the geo crate and comes back
+/// in `context`.
+pub fn simplify_vw<'c>(
+    context: &'c SimpleContextHandle,
+    geom: &SimpleGeometry,
+    area_tolerance: f64,
+) -> Result<SimpleGeometry<'c>> {
+    match geom.geometry_type() {
+        GeometryTypes::Polygon | GeometryTypes::MultiPolygon => {}
+        other => bail!("simplify_vw only handles polygons, not {:?}", other),
+    }
+
+    let simplified = match convert_geos_to_gdal(geom)?.to_rust_geo() {
+        Geometry::Polygon(p) => p.simplifyvw_preserve(&area_tolerance).to_gdal()?,
+        Geometry::MultiPolygon(mp) => mp.simplifyvw_preserve(&area_tolerance).to_gdal()?,
+        _ => bail!("unexpected geometry type after conversion"),
+    };
+
+    convert_from_gdal_to_geos(&simplified, context, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geos::SimpleCoordinateSequence;
+
+    /// 10x10 square with a 0.2 wide, 3 long spike on its bottom edge
+    fn spiky_square(context: &SimpleContextHandle) -> SimpleGeometry {
+        let ring = SimpleCoordinateSequence::from_slice_pts(
+            &[[0.0, 0.0], [5.0, 0.0], [5.1, -3.0], [5.2, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+            context,
+        ).unwrap();
+        SimpleGeometry::create_polygon(SimpleGeometry::create_linear_ring(ring).unwrap(), vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_simplify_vw() {
+        let context = SimpleContextHandle::new();
+        let polygon = spiky_square(&context);
+
+        // the spike is 3 units long, Douglas-Peucker with a tolerance of 1 keeps it
+        let dp = polygon.simplify(&context, 1.0, true).unwrap();
+        assert!(dp.total_coordinate_count().unwrap() > 5);
+
+        // but its area is only 0.3, Visvalingam drops it
+        let vw = simplify_vw(&context, &polygon, 1.0).unwrap();
+        assert_eq!(vw.geometry_type(), GeometryTypes::Polygon);
+        assert_eq!(vw.total_coordinate_count().unwrap(), 5);
+        assert!((vw.area().unwrap() - 100.0).abs() < 1e-9);
+
+        let line = SimpleGeometry::create_line_string(
+            SimpleCoordinateSequence::from_slice_pts(&[[0.0, 0.0], [1.0, 1.0]], &context).unwrap()
+        ).unwrap();
+        assert!(simplify_vw(&context, &line, 1.0).is_err());
+    }
+}
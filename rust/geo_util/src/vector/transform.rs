@@ -16,6 +16,7 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 use gdal::spatial_ref::{CoordTransform};
+use gdal::vector::{Feature, Layer};
 use geos::{SimpleGeometry, GeometryTypes, SimpleCoordinateSequence, SimpleContextHandle};
 use anyhow::{Result, bail};
 use itertools::Itertools;
@@ -110,6 +111,47 @@ pub fn transform_geos_polygon<'c>(
     SimpleGeometry::create_polygon(transformed_exterior, transformed_interior_rings)
 }
 
+/// Reprojects a coordinate sequence in place.  z is transformed too when the sequence has it,
+/// a 2D sequence is transformed as if its points were at height 0
+pub fn transform_coord_seq(
+    transform: &CoordTransform,
+    cs: &mut SimpleCoordinateSequence) -> Result<()>
+{
+    let num_points = cs.num_points()?;
+
+    let mut xs = Vec::with_capacity(num_points as _);
+    let mut ys = Vec::with_capacity(num_points as _);
+
+    for p in cs.points()?
+    {
+        xs.push(p[0]);
+        ys.push(p[1]);
+    }
+
+    if xs.len() != num_points as usize {
+        bail!("Could not read all {} points of the coordinate sequence", num_points);
+    }
+
+    let has_z = cs.has_z()?;
+    let mut zs = if has_z {
+        (0..num_points).map(|i| cs.get_z(i)).collect::<Result<Vec<_>>>()?
+    } else {
+        vec![0.0; num_points as usize]
+    };
+
+    transform.transform_coords(&mut xs, &mut ys, &mut zs)?;
+
+    for i in 0..num_points {
+        cs.set_x(i, xs[i as usize])?;
+        cs.set_y(i, ys[i as usize])?;
+        if has_z {
+            cs.set_z(i, zs[i as usize])?;
+        }
+    }
+
+    Ok(())
+}
+
 fn transform_linear_ring<'c>(
     context: &'c SimpleContextHandle,
     transform: &CoordTransform,
@@ -141,4 +183,160 @@ fn transform_linear_ring<'c>(
     }
 
     SimpleGeometry::create_linear_ring(coord_seq)
-}
\ No newline at end of file
+}
+
+/// Result of `reproject_layer`
+#[derive(Debug, Default)]
+pub struct ReprojectSummary {
+    /// Features written to the destination layer
+    pub copied: u64,
+    /// FID and error of the features whose geometry could not be transformed, they are not copied
+    pub failed: Vec<(i64, anyhow::Error)>,
+}
+
+/// Copies every feature of `src` into `dst` with its geometry transformed by `transform`, the
+/// source features are left untouched.  FIDs are kept and fields are matched by name, fields
+/// `dst` doesn't have are dropped.  A feature failing to transform is listed in `failed` and the
+/// copy goes on; errors writing to `dst` abort it
+pub fn reproject_layer(src: &Layer, dst: &mut Layer, transform: &CoordTransform) -> Result<ReprojectSummary> {
+    let src_defn = src.layer_definition();
+    let dst_defn = dst.layer_definition();
+
+    let field_map: Vec<(i32, i32)> = src_defn
+        .fields()
+        .enumerate()
+        .filter_map(|(src_idx, field)| {
+            dst_defn.get_field_index(&field.name()).ok().map(|dst_idx| (src_idx as i32, dst_idx))
+        })
+        .collect();
+
+    let mut summary = ReprojectSummary::default();
+
+    for src_feature in src.features() {
+        let fid = src_feature.fid();
+        let mut dst_feature = Feature::new(&dst_defn)?;
+
+        if src_feature.has_geometry() {
+            match src_feature.geometry().as_geom().transform(transform) {
+                Ok(geometry) => dst_feature.set_geometry_directly(geometry)?,
+                Err(err) => {
+                    summary.failed.push((fid, err));
+                    continue;
+                }
+            }
+        }
+
+        dst_feature.set_fid(fid)?;
+        for &(src_idx, dst_idx) in field_map.iter() {
+            if src_feature.is_field_set_and_not_null(src_idx) {
+                dst_feature.set_field_by_index(dst_idx, &src_feature.field_from_idx(src_idx)?)?;
+            }
+        }
+
+        dst_feature.create(dst)?;
+        summary.copied += 1;
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gdal::spatial_ref::SpatialRef;
+    use gdal::vector::{Dataset, FieldValue, Geometry, OGRFieldType, OGRwkbGeometryType};
+
+    #[test]
+    fn test_reproject_layer() {
+        let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+        let lv95 = SpatialRef::from_epsg(2056).unwrap();
+        let transform = CoordTransform::new(&wgs84, &lv95).unwrap();
+
+        let ds = Dataset::in_memory().unwrap();
+        let mut src = ds
+            .create_layer_ext::<&str>("src", &wgs84, OGRwkbGeometryType::wkbPoint, &[])
+            .unwrap();
+        src.create_defn_fields(&[("name", OGRFieldType::OFTString), ("height", OGRFieldType::OFTReal)])
+            .unwrap();
+        // the middle one has no valid latitude
+        let points = [
+            ("POINT (7.4386 46.9511)", "bern"),
+            ("POINT (7.0 100.0)", "nowhere"),
+            ("POINT (7.4386 46.9511)", "bern again"),
+        ];
+        for &(wkt, name) in points.iter() {
+            src.create_feature_fields(
+                Geometry::from_wkt(wkt).unwrap(),
+                &["name", "height"],
+                &[FieldValue::StringValue(name.to_string()), FieldValue::RealValue(10.0)],
+            ).unwrap();
+        }
+
+        // fields in another order and no height
+        let mut dst = ds
+            .create_layer_ext::<&str>("dst", &lv95, OGRwkbGeometryType::wkbPoint, &[])
+            .unwrap();
+        dst.create_defn_fields(&[("floors", OGRFieldType::OFTInteger), ("name", OGRFieldType::OFTString)])
+            .unwrap();
+
+        let summary = reproject_layer(&src, &mut dst, &transform).unwrap();
+        assert_eq!(summary.copied, 2);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, 1);
+
+        // FID 2 is only there if the FIDs were kept
+        let feature = dst.get_feature_by_id(2).unwrap();
+        assert_eq!(feature.field("name").unwrap().into_string(), Some("bern again".to_string()));
+        assert!(dst.layer_definition().get_field_index("height").is_err());
+        let [x, y] = feature.geometry().as_geom().get_point(0);
+        assert!((x - 2600000.0).abs() < 10.0 && (y - 1200000.0).abs() < 10.0);
+        assert!(dst.get_feature_by_id(1).is_err());
+
+        let src_feature = src.get_feature_by_id(0).unwrap();
+        assert_eq!(src_feature.geometry().as_geom().get_point(0), [7.4386, 46.9511]);
+    }
+
+    #[test]
+    fn test_transform_coord_seq() {
+        use geos::CoordDimensions;
+
+        let context = SimpleContextHandle::new();
+        let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+
+        let lv95 = SpatialRef::from_epsg(2056).unwrap();
+        let to_lv95 = CoordTransform::new(&wgs84, &lv95).unwrap();
+        let from_lv95 = CoordTransform::new(&lv95, &wgs84).unwrap();
+
+        let mut cs = SimpleCoordinateSequence::from_slice_pts(&[[7.4386, 46.9511], [8.5417, 47.3769]], &context).unwrap();
+        transform_coord_seq(&to_lv95, &mut cs).unwrap();
+        assert!((cs.get_x(0).unwrap() - 2600000.0).abs() < 10.0);
+        transform_coord_seq(&from_lv95, &mut cs).unwrap();
+        assert!(!cs.has_z().unwrap());
+        assert!((cs.get_x(1).unwrap() - 8.5417).abs() < 1e-7);
+        assert!((cs.get_y(1).unwrap() - 47.3769).abs() < 1e-7);
+
+        // geographic to geocentric, where the height changes x / y
+        let ecef = SpatialRef::from_epsg(4978).unwrap();
+        let to_ecef = CoordTransform::new(&wgs84, &ecef).unwrap();
+        let from_ecef = CoordTransform::new(&ecef, &wgs84).unwrap();
+
+        let mut cs = SimpleCoordinateSequence::new_with_dimensions(2, CoordDimensions::ThreeD, &context).unwrap();
+        for (i, &[x, y, z]) in [[7.4386, 46.9511, 540.0], [8.5417, 47.3769, 408.0]].iter().enumerate() {
+            cs.set_x(i as u32, x).unwrap();
+            cs.set_y(i as u32, y).unwrap();
+            cs.set_z(i as u32, z).unwrap();
+        }
+        let mut cs_2d = SimpleCoordinateSequence::from_slice_pts(&[[7.4386, 46.9511]], &context).unwrap();
+        transform_coord_seq(&to_ecef, &mut cs).unwrap();
+        transform_coord_seq(&to_ecef, &mut cs_2d).unwrap();
+        // 540 m higher is further from the center of the earth
+        assert!(cs.get_x(0).unwrap() > cs_2d.get_x(0).unwrap());
+
+        transform_coord_seq(&from_ecef, &mut cs).unwrap();
+        assert!(cs.has_z().unwrap());
+        assert!((cs.get_x(0).unwrap() - 7.4386).abs() < 1e-7);
+        assert!((cs.get_y(0).unwrap() - 46.9511).abs() < 1e-7);
+        assert!((cs.get_z(0).unwrap() - 540.0).abs() < 1e-3);
+        assert!((cs.get_z(1).unwrap() - 408.0).abs() < 1e-3);
+    }
+}
@@ -247,6 +247,14 @@ extern "C" {
     pub fn GEOSDifference(g1: *const GEOSGeometry, g2: *const GEOSGeometry) -> *mut GEOSGeometry;
     pub fn GEOSUnion(g1: *const GEOSGeometry, g2: *const GEOSGeometry) -> *mut GEOSGeometry;
     pub fn GEOSUnaryUnion(g: *const GEOSGeometry) -> *mut GEOSGeometry;
+    #[cfg(feature = "v3_8_0")]
+    pub fn GEOSCoverageUnion(g: *const GEOSGeometry) -> *mut GEOSGeometry;
+    #[cfg(feature = "v3_12_0")]
+    pub fn GEOSCoverageIsValid(
+        input: *const GEOSGeometry,
+        gapWidth: c_double,
+        invalidEdges: *mut *mut GEOSGeometry,
+    ) -> c_int;
     pub fn GEOSClipByRect(
         g: *const GEOSGeometry,
         xmin: c_double,
@@ -668,6 +676,18 @@ extern "C" {
         handle: GEOSContextHandle_t,
         g: *const GEOSGeometry,
     ) -> *mut GEOSGeometry;
+    #[cfg(feature = "v3_8_0")]
+    pub fn GEOSCoverageUnion_r(
+        handle: GEOSContextHandle_t,
+        g: *const GEOSGeometry,
+    ) -> *mut GEOSGeometry;
+    #[cfg(feature = "v3_12_0")]
+    pub fn GEOSCoverageIsValid_r(
+        handle: GEOSContextHandle_t,
+        input: *const GEOSGeometry,
+        gapWidth: c_double,
+        invalidEdges: *mut *mut GEOSGeometry,
+    ) -> c_int;
     pub fn GEOSVoronoiDiagram_r(
         handle: GEOSContextHandle_t,
         g: *const GEOSGeometry,
@@ -48,6 +48,7 @@ pub use enums::{
 
 pub use functions::{ version};
 pub use simple_wkb_writer::WKBWriter;
+pub use simple_wkt_writer::WKTWriter;
 pub use simple_wkb_reader::WKBReader;
 
 //#[cfg(any(feature = "geo", feature = "dox"))]
@@ -58,6 +59,7 @@ pub use simple_wkb_reader::WKBReader;
 
 mod enums;
 mod simple_wkb_writer;
+mod simple_wkt_writer;
 mod simple_wkb_reader;
 mod prepared_geometry;
 
@@ -30,10 +30,19 @@ unsafe extern "C" fn message_handler_func(
 }
 
 
+/// Owns a GEOS context created with `GEOS_init_r`.
+///
+/// A context is not thread safe: it (and every geometry created with it) must only be used from
+/// the thread that created it, which the raw handle enforces by making this type `!Send` and
+/// `!Sync`.  Parallel code creates one context per worker thread.
 pub struct SimpleContextHandle {
     pub(crate) c_handle: GEOSContextHandle_t
 }
 
+thread_local! {
+    static THREAD_DEFAULT: SimpleContextHandle = SimpleContextHandle::new();
+}
+
 impl SimpleContextHandle {
     pub fn new() -> Self {
         unsafe {
@@ -43,6 +52,17 @@ impl SimpleContextHandle {
         }
     }
 
+    /// Runs `f` with the context shared by all callers on the current thread, created on first
+    /// use, so single threaded code does not have to keep a context around.
+    ///
+    /// Each thread gets its own context, finished when the thread exits.  Geometries created
+    /// with it can't outlive `f`.
+    pub fn with_thread_default<F, R>(f: F) -> R
+        where F: FnOnce(&SimpleContextHandle) -> R
+    {
+        THREAD_DEFAULT.with(f)
+    }
+
     pub fn add_message_handlers(&self) {
         unsafe {
             GEOSContext_setNoticeMessageHandler_r(self.c_handle, Some(message_handler_func), null_mut() );
@@ -18,6 +18,8 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 use geos_sys::*;
 use anyhow::{bail, Result};
 use SimpleContextHandle;
+use CoordDimensions;
+use enums::TryFrom;
 
 pub struct SimpleCoordinateSequence<'c>
 {
@@ -28,8 +30,13 @@ pub struct SimpleCoordinateSequence<'c>
 
 impl <'c> SimpleCoordinateSequence<'c> {
     pub fn new(length: u32, context_handle: &'c SimpleContextHandle) -> Result<Self> {
+        Self::new_with_dimensions(length, CoordDimensions::TwoD, context_handle)
+    }
+
+    /// Like `new`, `CoordDimensions::ThreeD` gives a sequence with Z values
+    pub fn new_with_dimensions(length: u32, dimensions: CoordDimensions, context_handle: &'c SimpleContextHandle) -> Result<Self> {
         unsafe {
-            let ptr = GEOSCoordSeq_create_r(context_handle.c_handle, length, 2);
+            let ptr = GEOSCoordSeq_create_r(context_handle.c_handle, length, dimensions.into());
 
             if ptr.is_null() {
                 bail!("GEOSCoordSeq_create_r");
@@ -66,12 +73,32 @@ impl <'c> SimpleCoordinateSequence<'c> {
         }
     }
 
+    /// Number of ordinates per point, 3 when the sequence has Z values
+    pub fn dimensions(&self) -> Result<CoordDimensions> {
+        let mut n = 0;
+        let ret_val =
+            unsafe { GEOSCoordSeq_getDimensions_r(self.context_handle.c_handle, self.c_handle, &mut n) };
+        if ret_val == 0 {
+            bail!("getting dimensions from CoordSeq");
+        }
+        match CoordDimensions::try_from(n) {
+            Ok(dimensions) => Ok(dimensions),
+            Err(e) => bail!("{}: {}", e, n),
+        }
+    }
+
+    pub fn has_z(&self) -> Result<bool> {
+        Ok(self.dimensions()? == CoordDimensions::ThreeD)
+    }
+
     pub fn points(&self) -> Result<PointIterator> {
         PointIterator::new(self)
     }
 
     /// Gets the X position value at the given `line`.
     pub fn get_x(&self, index: u32) -> Result<f64> {
+        // GEOS doesn't check the index, reading past the end of i.e. the ring of an empty polygon crashes
+        self.check_index(index)?;
 
         let mut n = 0.;
         let ret_val = unsafe {
@@ -86,6 +113,8 @@ impl <'c> SimpleCoordinateSequence<'c> {
 
     /// Gets the Y position value at the given `line`.
     pub fn get_y(&self, index: u32) -> Result<f64> {
+        // GEOS doesn't check the index, reading past the end of i.e. the ring of an empty polygon crashes
+        self.check_index(index)?;
 
         let mut n = 0.;
         let ret_val = unsafe {
@@ -98,6 +127,29 @@ impl <'c> SimpleCoordinateSequence<'c> {
         }
     }
 
+    /// Gets the Z position value at the given `line`, NaN when the sequence has no Z.
+    pub fn get_z(&self, index: u32) -> Result<f64> {
+        self.check_index(index)?;
+
+        let mut n = 0.;
+        let ret_val = unsafe {
+            GEOSCoordSeq_getZ_r(self.context_handle.c_handle, self.c_handle, index, &mut n)
+        };
+        if ret_val == 0 {
+            bail!("failed to get coordinates from CoordSeq");
+        } else {
+            Ok(n)
+        }
+    }
+
+    fn check_index(&self, index: u32) -> Result<()> {
+        let num_points = self.num_points()?;
+        if index >= num_points {
+            bail!("index {} out of range for a CoordSeq of {} points", index, num_points);
+        }
+        Ok(())
+    }
+
     pub fn is_ccw(&self) -> Result<bool> {
 
         let mut is_ccw_ret: i8 = 0;
@@ -135,6 +187,19 @@ impl <'c> SimpleCoordinateSequence<'c> {
             Ok(())
         }
     }
+
+    /// Sets the Z position value
+    pub fn set_z(&mut self, index: u32, value: f64) -> Result<()> {
+
+        let ret_val = unsafe {
+            GEOSCoordSeq_setZ_r(self.context_handle.c_handle, self.c_handle, index, value)
+        };
+        if ret_val == 0 {
+            bail!("failed to set coordinates from CoordSeq");
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl <'c> Drop for SimpleCoordinateSequence<'c> {
@@ -17,11 +17,12 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 use geos_sys::*;
 use crate::SimpleContextHandle;
-use ::{GeometryTypes, SimpleCoordinateSequence};
+use ::{GeometryTypes, OutputDimension, SimpleCoordinateSequence, WKBWriter, WKTWriter};
 use anyhow::{bail, Result};
 use simple_string::simple_managed_string;
 use ByteOrder;
 use c_vec::CVec;
+use prepared_geometry::check_geos_predicate;
 
 pub struct SimpleGeometry<'c>
 {
@@ -49,6 +50,26 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Axis aligned rectangle polygon from bounds, i.e. for a clip mask or a spatial filter
+    pub fn create_rectangle(context: &'c SimpleContextHandle,
+                            min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Result<Self>
+    {
+        if min_x > max_x || min_y > max_y {
+            bail!("Invalid rectangle bounds {} {} {} {}", min_x, min_y, max_x, max_y);
+        }
+
+        let coord_seq = SimpleCoordinateSequence::from_slice_pts(&[
+            [min_x, min_y],
+            [max_x, min_y],
+            [max_x, max_y],
+            [min_x, max_y],
+            [min_x, min_y],
+        ], context)?;
+
+        let ring = SimpleGeometry::create_linear_ring(coord_seq)?;
+        SimpleGeometry::create_polygon(ring, vec![])
+    }
+
     pub fn create_multi_geom(
         context: &'c SimpleContextHandle,
         mut geoms: Vec<SimpleGeometry<'c>>,
@@ -219,6 +240,9 @@ impl <'c> SimpleGeometry<'c> {
     //Must call envelope first
     pub fn bbox(&self) -> Result< [f64;4] >
     {
+        if self.is_empty()? {
+            bail!("bbox of an empty geometry");
+        }
         let exterior_ring = self.get_exterior_ring()?;
         let coord_seq = exterior_ring.get_coord_sequence()?;
         //the envelope has a specific order
@@ -263,21 +287,40 @@ impl <'c> SimpleGeometry<'c> {
     }
 
     pub fn get_xy(&self) -> Result< (f64, f64) > {
-        unsafe {
-            let mut x: f64 = 0.0;
-            let mut y: f64 = 0.0;
-            let mut status = GEOSGeomGetX_r(self.context_handle.c_handle, self.c_handle, &mut x);
-            if status < 0 {
-                bail!("Error");
-            }
+        Ok((self.x()?, self.y()?))
+    }
 
-            status = GEOSGeomGetY_r(self.context_handle.c_handle, self.c_handle, &mut y);
-            if status < 0 {
-                bail!("Error");
-            }
+    /// X of a point geometry, errors for any other type or an empty point
+    pub fn x(&self) -> Result<f64> {
+        self.check_point("x")?;
+        let mut x: f64 = 0.0;
+        let status = unsafe { GEOSGeomGetX_r(self.context_handle.c_handle, self.c_handle, &mut x) };
+        if status == 0 {
+            bail!("GEOSGeomGetX_r");
+        }
+        Ok(x)
+    }
 
-            return Ok((x, y));
+    /// Y of a point geometry, errors for any other type or an empty point
+    pub fn y(&self) -> Result<f64> {
+        self.check_point("y")?;
+        let mut y: f64 = 0.0;
+        let status = unsafe { GEOSGeomGetY_r(self.context_handle.c_handle, self.c_handle, &mut y) };
+        if status == 0 {
+            bail!("GEOSGeomGetY_r");
         }
+        Ok(y)
+    }
+
+    fn check_point(&self, method_name: &str) -> Result<()> {
+        let geometry_type = self.geometry_type();
+        if geometry_type != GeometryTypes::Point {
+            bail!("{} is only defined for points, not {:?}", method_name, geometry_type);
+        }
+        if self.is_empty()? {
+            bail!("{} of an empty point", method_name);
+        }
+        Ok(())
     }
 
     pub fn centroid(&self) -> Result<Self> {
@@ -339,6 +382,16 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Number of coordinates of all the parts and rings together, a cheap measure of how costly
+    /// the geometry is to process (one big multipolygon can outweigh thousands of small ones)
+    pub fn total_coordinate_count(&self) -> Result<usize> {
+        let ret = unsafe { GEOSGetNumCoordinates_r(self.context_handle.c_handle, self.c_handle) };
+        if ret < 0 {
+            bail!("GEOSGetNumCoordinates_r failed");
+        }
+        Ok(ret as _)
+    }
+
     pub fn get_type(&self) -> Result<String> {
         unsafe {
             let ptr = GEOSGeomType_r(self.context_handle.c_handle, self.c_handle);
@@ -394,6 +447,48 @@ impl <'c> SimpleGeometry<'c> {
     }
 
     pub fn get_coord_sequence(&self) -> Result<SimpleCoordinateSequence> {
+        self.coord_sequence_of_simple_geom()
+    }
+
+    /// All the coordinate sequences of the geometry, the exterior then the interior rings
+    /// for polygons, each part in turn for multi geometries and collections
+    pub fn coord_seqs<'s>(&'s self) -> Result<Vec<SimpleCoordinateSequence<'s>>> {
+        let mut seqs = Vec::new();
+        self.collect_coord_seqs(&mut seqs)?;
+        Ok(seqs)
+    }
+
+    fn collect_coord_seqs(&self, seqs: &mut Vec<SimpleCoordinateSequence<'c>>) -> Result<()> {
+        match self.geometry_type() {
+            GeometryTypes::Point | GeometryTypes::LineString | GeometryTypes::LinearRing => {
+                seqs.push(self.coord_sequence_of_simple_geom()?);
+            }
+            GeometryTypes::Polygon => {
+                seqs.push(self.get_exterior_ring()?.coord_sequence_of_simple_geom()?);
+                for n in 0..self.get_num_interior_rings()? {
+                    seqs.push(self.get_interior_ring_n(n as _)?.coord_sequence_of_simple_geom()?);
+                }
+            }
+            GeometryTypes::MultiPoint | GeometryTypes::MultiLineString
+            | GeometryTypes::MultiPolygon | GeometryTypes::GeometryCollection => {
+                for n in 0..self.get_num_geometries()? {
+                    let sub: SimpleGeometry<'c> = self.get_geometry_n(n)?;
+                    sub.collect_coord_seqs(seqs)?;
+                }
+            }
+            GeometryTypes::__Unknonwn(t) => bail!("Unknown geometry type {}", t),
+        }
+        Ok(())
+    }
+
+    /// The sequence is owned by the geometry (which lives at least as long as the context),
+    /// callers must tie the result to the lifetime of the geometry
+    fn coord_sequence_of_simple_geom(&self) -> Result<SimpleCoordinateSequence<'c>> {
+        match self.geometry_type() {
+            GeometryTypes::Point | GeometryTypes::LineString | GeometryTypes::LinearRing => {}
+            other => bail!("get_coord_sequence: geometry must be a Point, LineString or LinearRing \
+                but was {:?}, use coord_seqs for polygons and multi geometries", other),
+        }
         unsafe {
             let ptr = GEOSGeom_getCoordSeq_r(self.context_handle.c_handle, self.c_handle);
             if ptr.is_null() {
@@ -432,6 +527,8 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Solid footprint of a polygon, only the exterior ring is kept.  For a MultiPolygon
+    /// the holes of every polygon are removed.  Unlike make_valid the shell is unchanged
     pub fn remove_holes(&self, context: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>>
     {
 
@@ -443,11 +540,11 @@ impl <'c> SimpleGeometry<'c> {
             GeometryTypes::MultiPolygon => {
                 let poly_count = self.get_num_geometries()?;
                 let polygons = (0..poly_count)
-                    .map(|n| self.get_geometry_n(n).unwrap().remove_holes(context).unwrap() )
-                    .collect();
+                    .map(|n| self.get_geometry_n(n)?.remove_holes(context) )
+                    .collect::<Result<Vec<_>>>()?;
                 SimpleGeometry::create_multi_geom(context, polygons, GeometryTypes::MultiPolygon)?
             }
-            _ => bail!("Not a multipolygon nor polygon")
+            other => bail!("remove_holes: not a multipolygon nor polygon but {:?}", other)
         })
     }
 
@@ -492,6 +589,72 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Union of a collection of polygons forming a coverage: they may share edges but must not
+    /// overlap or leave slivers between them.  Much faster than `union_unary`, the result is
+    /// undefined for an invalid coverage
+    #[cfg(any(feature = "v3_8_0", feature = "dox"))]
+    pub fn coverage_union(&self, context: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>>
+    {
+        unsafe {
+            let ptr = GEOSCoverageUnion_r(
+                context.c_handle,
+                self.c_handle,
+            );
+
+            if ptr.is_null() {
+                bail!("GEOSCoverageUnion_r exception");
+            }
+
+            Ok(SimpleGeometry{
+                c_handle: ptr,
+                owned: true,
+                context_handle: context
+            })
+        }
+    }
+
+    /// Checks that the polygons of this collection form a valid coverage: no overlaps, and no
+    /// gaps narrower than `gap_width` (0 to only check that shared edges match exactly)
+    #[cfg(any(feature = "v3_12_0", feature = "dox"))]
+    pub fn coverage_is_valid(&self, gap_width: f64) -> Result<bool>
+    {
+        let rv = unsafe {
+            GEOSCoverageIsValid_r(
+                self.context_handle.c_handle,
+                self.c_handle,
+                gap_width,
+                std::ptr::null_mut(),
+            )
+        };
+        match rv {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => bail!("GEOSCoverageIsValid_r exception"),
+        }
+    }
+
+    /// Joins the linestrings of self into the fewest possible maximal linestrings.  Segments are only
+    /// merged through nodes shared by exactly two of them, so branching junctions stay split
+    pub fn line_merge(&self, context: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>>
+    {
+        unsafe {
+            let ptr = GEOSLineMerge_r(
+                context.c_handle,
+                self.c_handle,
+            );
+
+            if ptr.is_null() {
+                bail!("GEOSLineMerge_r exception");
+            }
+
+            Ok(SimpleGeometry{
+                c_handle: ptr,
+                owned: true,
+                context_handle: context
+            })
+        }
+    }
+
     pub fn difference(&self, context: &'c SimpleContextHandle, rhs: &SimpleGeometry) -> Result<SimpleGeometry<'c>>
     {
         unsafe {
@@ -534,6 +697,29 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Linework shared by two lineal geometries, as a GeometryCollection of two MultiLineStrings:
+    /// the paths going in the same direction in both, then those going in opposite directions
+    pub fn shared_paths(&self, context: &'c SimpleContextHandle, rhs: &SimpleGeometry) -> Result<SimpleGeometry<'c>>
+    {
+        unsafe {
+            let ptr = GEOSSharedPaths_r(
+                context.c_handle,
+                self.c_handle,
+                rhs.c_handle
+            );
+
+            if ptr.is_null() {
+                bail!("GEOSSharedPaths_r exception");
+            }
+
+            Ok(SimpleGeometry{
+                c_handle: ptr,
+                owned: true,
+                context_handle: context
+            })
+        }
+    }
+
     pub fn intersects(&self, rhs: &SimpleGeometry) -> Result<bool>
     {
         unsafe {
@@ -555,6 +741,8 @@ impl <'c> SimpleGeometry<'c> {
 
     /// quadsegs is how many lines per quater circle -- 8 is a good start
     /// Use a new lifetime since the returned geometry depends on the passed in context lifetime
+    ///
+    /// A negative width larger than half the polygon width gives an empty polygon, check `is_empty`
     pub fn buffer<'d>(&self, context: &'d SimpleContextHandle,
                   width: f64, quadsegs: i32) -> Result<SimpleGeometry<'d>> {
         assert!(quadsegs > 0);
@@ -625,6 +813,56 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Returns `true` if both geometries have the same structure and their vertices
+    /// are all within `tolerance` of each other.  Unlike `GEOSEquals` this is not
+    /// topological, vertex order matters.
+    pub fn equals_exact(&self, other: &SimpleGeometry, tolerance: f64) -> Result<bool>
+    {
+        let ret_val = unsafe {
+            GEOSEqualsExact_r(self.context_handle.c_handle, self.c_handle, other.c_handle, tolerance)
+        };
+        check_geos_predicate(ret_val)
+    }
+
+    /// Returns `true` if no point of `other` is outside of `self`.
+    pub fn covers(&self, other: &SimpleGeometry) -> Result<bool>
+    {
+        let ret_val = unsafe {
+            GEOSCovers_r(self.context_handle.c_handle, self.c_handle, other.c_handle)
+        };
+        check_geos_predicate(ret_val)
+    }
+
+    /// True if the first and last points of a LineString / LinearRing (or of every line of a
+    /// MultiLineString) are the same, errors for other geometry types
+    pub fn is_closed(&self) -> Result<bool>
+    {
+        let ret_val = unsafe {
+            GEOSisClosed_r(self.context_handle.c_handle, self.c_handle)
+        };
+        check_geos_predicate(ret_val)
+    }
+
+    /// Returns `true` if no point of `self` is outside of `other`, same as `other.covers(self)`.
+    pub fn covered_by(&self, other: &SimpleGeometry) -> Result<bool>
+    {
+        let ret_val = unsafe {
+            GEOSCoveredBy_r(self.context_handle.c_handle, self.c_handle, other.c_handle)
+        };
+        check_geos_predicate(ret_val)
+    }
+
+    /// Returns `true` if `self` is inside `other` and their interiors intersect, same as
+    /// `other.contains(self)`.  Unlike `covered_by`, a geometry lying only on the boundary
+    /// of `other` is not within it.
+    pub fn within(&self, other: &SimpleGeometry) -> Result<bool>
+    {
+        let ret_val = unsafe {
+            GEOSWithin_r(self.context_handle.c_handle, self.c_handle, other.c_handle)
+        };
+        check_geos_predicate(ret_val)
+    }
+
     pub fn create_empty_collection(context: &'c SimpleContextHandle, geom_type: GeometryTypes) -> Result<Self> {
         match geom_type {
             GeometryTypes::GeometryCollection
@@ -692,18 +930,11 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// WKT rounded to `precision` decimals without trailing zeros, i.e. `POINT (1.5 2)`.
+    /// Stable to compare in tests, see WKTWriter for more options
     pub fn to_wkt_precision(&self, precision: u32) -> Result<String> {
-        unsafe {
-            let writer = GEOSWKTWriter_create_r(self.context_handle.c_handle);
-            GEOSWKTWriter_setRoundingPrecision_r(
-                self.context_handle.c_handle,
-                writer,
-                precision as _,
-            );
-            let c_result = GEOSWKTWriter_write_r(self.context_handle.c_handle, writer, self.c_handle);
-            GEOSWKTWriter_destroy_r(self.context_handle.c_handle, writer);
-            simple_managed_string(c_result, self.context_handle)
-        }
+        let writer = WKTWriter::new_with_precision(self.context_handle, precision)?;
+        writer.write(self)
     }
 
     pub fn area(&self) -> Result<f64> {
@@ -718,11 +949,121 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Smallest distance a vertex can be moved to make the geometry invalid (or collapse it).
+    /// Very small values flag thin slivers, infinity is returned when no vertex move can
+    /// invalidate the geometry, i.e. for a single point
+    #[cfg(any(feature = "v3_6_0", feature = "dox"))]
+    pub fn minimum_clearance(&self) -> Result<f64> {
+        let mut d = 0.;
+        let ret_val = unsafe {
+            GEOSMinimumClearance_r(self.context_handle.c_handle, self.c_handle, &mut d)
+        };
+        if ret_val != 0 {
+            bail!("GEOSMinimumClearance_r failed");
+        }
+        Ok(d)
+    }
+
+    /// Largest distance from a point of one geometry to the closest point of the other, only
+    /// using the vertices.  0 for identical shapes, a shape similarity score
+    pub fn hausdorff_distance(&self, other: &SimpleGeometry) -> Result<f64> {
+        let mut d = 0.;
+        let ret_val = unsafe {
+            GEOSHausdorffDistance_r(self.context_handle.c_handle, self.c_handle, other.c_handle, &mut d)
+        };
+        if ret_val != 1 {
+            bail!("GEOSHausdorffDistance_r failed");
+        }
+        Ok(d)
+    }
+
+    /// Same as `hausdorff_distance` with the segments split in parts of `densify_frac` (0 to 1)
+    /// of their length, more precise when the vertices alone miss the largest distance
+    pub fn hausdorff_distance_densify(&self, other: &SimpleGeometry, densify_frac: f64) -> Result<f64> {
+        let mut d = 0.;
+        let ret_val = unsafe {
+            GEOSHausdorffDistanceDensify_r(self.context_handle.c_handle, self.c_handle, other.c_handle, densify_frac, &mut d)
+        };
+        if ret_val != 1 {
+            bail!("GEOSHausdorffDistanceDensify_r failed");
+        }
+        Ok(d)
+    }
+
+    /// Like `hausdorff_distance` but follows the order of the points along both geometries
+    #[cfg(any(feature = "v3_7_0", feature = "dox"))]
+    pub fn frechet_distance(&self, other: &SimpleGeometry) -> Result<f64> {
+        let mut d = 0.;
+        let ret_val = unsafe {
+            GEOSFrechetDistance_r(self.context_handle.c_handle, self.c_handle, other.c_handle, &mut d)
+        };
+        if ret_val != 1 {
+            bail!("GEOSFrechetDistance_r failed");
+        }
+        Ok(d)
+    }
+
+    /// `frechet_distance` with the segments split in parts of `densify_frac` (0 to 1) of their length
+    #[cfg(any(feature = "v3_7_0", feature = "dox"))]
+    pub fn frechet_distance_densify(&self, other: &SimpleGeometry, densify_frac: f64) -> Result<f64> {
+        let mut d = 0.;
+        let ret_val = unsafe {
+            GEOSFrechetDistanceDensify_r(self.context_handle.c_handle, self.c_handle, other.c_handle, densify_frac, &mut d)
+        };
+        if ret_val != 1 {
+            bail!("GEOSFrechetDistanceDensify_r failed");
+        }
+        Ok(d)
+    }
+
+    /// Width of the geometry, the length of the smallest diameter of its convex hull
+    #[cfg(any(feature = "v3_6_0", feature = "dox"))]
+    pub fn minimum_width(&self) -> Result<f64> {
+        unsafe {
+            let line = GEOSMinimumWidth_r(self.context_handle.c_handle, self.c_handle);
+            if line.is_null() {
+                bail!("GEOSMinimumWidth_r");
+            }
+            let mut length = 0.;
+            let ret_val = GEOSLength_r(self.context_handle.c_handle, line, &mut length);
+            GEOSGeom_destroy_r(self.context_handle.c_handle, line);
+            if ret_val != 1 {
+                bail!("GEOSLength_r failed");
+            }
+            Ok(length)
+        }
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        let rv = unsafe { GEOSisEmpty_r(self.context_handle.c_handle, self.c_handle) };
+        check_geos_predicate(rv as _)
+    }
+
     pub fn is_valid(&self) -> bool {
         unsafe { GEOSisValid_r(self.context_handle.c_handle,
                                self.c_handle) == 1 }
     }
 
+    /// Builds the polygons enclosed by the linework of this geometry, i.e. a collection of
+    /// boundary rings.  Rings inside other rings become holes.
+    #[cfg(any(feature = "v3_8_0", feature = "dox"))]
+    pub fn build_area(&self, context_handle: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>>
+    {
+        unsafe {
+            let ptr = GEOSBuildArea_r(context_handle.c_handle, self.c_handle);
+
+            if ptr.is_null() {
+                bail!("GEOSBuildArea_r");
+            }
+
+            Ok(SimpleGeometry{
+                c_handle: ptr,
+                owned: true,
+                context_handle
+            })
+        }
+    }
+
     pub fn make_valid(&self, context_handle: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>>
     {
         unsafe {
@@ -743,6 +1084,35 @@ impl <'c> SimpleGeometry<'c> {
     }
     //
 
+    /// 2 for XY geometries, 3 if the geometry has Z values
+    pub fn coordinate_dimension(&self) -> Result<u32> {
+        let dimension = unsafe { GEOSGeom_getCoordinateDimension_r(self.context_handle.c_handle, self.c_handle) };
+        if dimension == 0 {
+            bail!("GEOSGeom_getCoordinateDimension_r failed");
+        }
+        Ok(dimension as u32)
+    }
+
+    /// Copy of the geometry with the Z values dropped
+    pub fn force_2d(&self, context_handle: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>> {
+        let mut writer = WKBWriter::new(context_handle)?;
+        writer.set_output_dimension(OutputDimension::TwoD);
+        let wkb = writer.write_wkb(self)?;
+
+        unsafe {
+            let ptr = GEOSGeomFromWKB_buf_r(context_handle.c_handle, wkb.as_ref().as_ptr(), wkb.len());
+            if ptr.is_null() {
+                bail!("GEOSGeomFromWKB_buf_r");
+            }
+
+            Ok(SimpleGeometry {
+                c_handle: ptr,
+                owned: true,
+                context_handle
+            })
+        }
+    }
+
     pub fn set_srid(&self, srid: i32) {
         assert!(self.owned);
 
@@ -757,6 +1127,11 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// SRID of the geometry, 0 if none was set
+    pub fn srid(&self) -> i32 {
+        self.get_srid()
+    }
+
     pub fn set_precision(&self, context_handle: &'c SimpleContextHandle,
                          grid_size: f64) -> Result<SimpleGeometry<'c>> {
         unsafe {
@@ -775,6 +1150,13 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Rounds every coordinate to a multiple of `size` (collapsed components are dropped), so two
+    /// geometries differing only below the grid size become coordinate-identical.
+    /// Not to be confused with snapping to another geometry
+    pub fn snap_to_grid(&self, context_handle: &'c SimpleContextHandle, size: f64) -> Result<SimpleGeometry<'c>> {
+        self.set_precision(context_handle, size)
+    }
+
     pub fn get_precision(&self) -> f64 {
         unsafe {
             GEOSGeom_getPrecision_r(self.context_handle.c_handle,
@@ -809,6 +1191,7 @@ impl <'c> SimpleGeometry<'c> {
 
     }
 
+    /// Independent, owned copy of self created in `context_handle`
     pub fn clone(&self, context_handle: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>>
     {
         unsafe {
@@ -825,6 +1208,96 @@ impl <'c> SimpleGeometry<'c> {
         }
     }
 
+    /// Deep copy in the context of self, always owned even if self is a borrowed part of another
+    /// geometry
+    pub fn clone_geom(&self) -> Result<SimpleGeometry<'c>> {
+        self.clone(self.context_handle)
+    }
+
+    /// Unwraps arbitrarily nested collections into a single flat GeometryCollection whose members
+    /// are only points, linestrings and polygons (cloned, so `self` is left untouched)
+    pub fn flatten_collection(&self, context_handle: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>> {
+        let mut primitives = Vec::new();
+        self.collect_primitives(context_handle, &mut primitives)?;
+
+        SimpleGeometry::create_multi_geom(context_handle, primitives, GeometryTypes::GeometryCollection)
+    }
+
+    fn collect_primitives(&self, context_handle: &'c SimpleContextHandle, primitives: &mut Vec<SimpleGeometry<'c>>) -> Result<()> {
+        match self.geometry_type() {
+            GeometryTypes::MultiPoint | GeometryTypes::MultiLineString |
+            GeometryTypes::MultiPolygon | GeometryTypes::GeometryCollection => {
+                // not get_num_geometries, an empty collection is fine here
+                let num_geometries = unsafe { GEOSGetNumGeometries_r(self.context_handle.c_handle, self.c_handle) };
+                if num_geometries < 0 {
+                    bail!("GEOSGetNumGeometries_r failed");
+                }
+                for i in 0..num_geometries as usize {
+                    self.get_geometry_n(i)?.collect_primitives(context_handle, primitives)?;
+                }
+            },
+            _ => primitives.push(self.clone(context_handle)?),
+        }
+        Ok(())
+    }
+
+    /// Inserts evenly spaced vertices so no segment is longer than `max_segment_length`, the shape
+    /// itself is unchanged.  Only x and y are kept
+    pub fn densify(&self, context_handle: &'c SimpleContextHandle, max_segment_length: f64) -> Result<SimpleGeometry<'c>> {
+        if !(max_segment_length > 0.0) {
+            bail!("max_segment_length must be positive, got {}", max_segment_length);
+        }
+        if self.is_empty()? {
+            return self.clone(context_handle);
+        }
+
+        match self.geometry_type() {
+            GeometryTypes::Point | GeometryTypes::MultiPoint => self.clone(context_handle),
+            GeometryTypes::LineString => {
+                SimpleGeometry::create_line_string(self.densified_coords(context_handle, max_segment_length)?)
+            },
+            GeometryTypes::LinearRing => {
+                SimpleGeometry::create_linear_ring(self.densified_coords(context_handle, max_segment_length)?)
+            },
+            GeometryTypes::Polygon => {
+                let exterior = self.get_exterior_ring()?.densify(context_handle, max_segment_length)?;
+                let mut interiors = Vec::new();
+                for n in 0..self.get_num_interior_rings()? {
+                    interiors.push(self.get_interior_ring_n(n as u32)?.densify(context_handle, max_segment_length)?);
+                }
+                SimpleGeometry::create_polygon(exterior, interiors)
+            },
+            geom_type => {
+                let mut geoms = Vec::new();
+                for n in 0..self.get_num_geometries()? {
+                    geoms.push(self.get_geometry_n(n)?.densify(context_handle, max_segment_length)?);
+                }
+                SimpleGeometry::create_multi_geom(context_handle, geoms, geom_type)
+            }
+        }
+    }
+
+    fn densified_coords(&self, context_handle: &'c SimpleContextHandle, max_segment_length: f64) -> Result<SimpleCoordinateSequence<'c>> {
+        let coord_seq = self.get_coord_sequence()?;
+        let num_points = coord_seq.num_points()?;
+
+        let mut pts: Vec<[f64; 2]> = Vec::with_capacity(num_points as usize);
+        for i in 0..num_points {
+            let pt = [coord_seq.get_x(i)?, coord_seq.get_y(i)?];
+            if let Some(prev) = pts.last().cloned() {
+                let (dx, dy) = (pt[0] - prev[0], pt[1] - prev[1]);
+                let n_segments = ((dx * dx + dy * dy).sqrt() / max_segment_length).ceil() as u32;
+                for s in 1..n_segments {
+                    let f = s as f64 / n_segments as f64;
+                    pts.push([prev[0] + f * dx, prev[1] + f * dy]);
+                }
+            }
+            pts.push(pt);
+        }
+
+        SimpleCoordinateSequence::from_slice_pts(&pts, context_handle)
+    }
+
     pub fn polygon_to_multipolygon(&self, context_handle: &'c SimpleContextHandle) -> Result<SimpleGeometry<'c>> {
 
         unsafe {
@@ -914,4 +1387,394 @@ impl <'c> Drop for SimpleGeometry<'c> {
 }
 
 
-//Conversions from
\ No newline at end of file
+//Conversions from
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multi_line_string<'c>(context: &'c SimpleContextHandle, lines: &[&[[f64; 2]]]) -> SimpleGeometry<'c> {
+        let lines = lines.iter().map(|pts| {
+            let cs = SimpleCoordinateSequence::from_slice_pts(pts, context).unwrap();
+            SimpleGeometry::create_line_string(cs).unwrap()
+        }).collect();
+        SimpleGeometry::create_multi_geom(context, lines, GeometryTypes::MultiLineString).unwrap()
+    }
+
+    #[test]
+    fn test_line_merge() {
+        let context = SimpleContextHandle::new();
+
+        // a chain of 3 segments, given out of order
+        let chain = multi_line_string(&context, &[
+            &[[1.0, 0.0], [2.0, 0.0]],
+            &[[0.0, 0.0], [1.0, 0.0]],
+            &[[2.0, 0.0], [3.0, 0.0]],
+        ]);
+        let merged = chain.line_merge(&context).unwrap();
+        assert_eq!(merged.geometry_type(), GeometryTypes::LineString);
+        let cs = merged.get_coord_sequence().unwrap();
+        assert_eq!(cs.num_points().unwrap(), 4);
+        let mut ends = [cs.get_x(0).unwrap(), cs.get_x(3).unwrap()];
+        ends.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(ends, [0.0, 3.0]);
+
+        // 3 segments meeting at a junction can't be merged
+        let junction = multi_line_string(&context, &[
+            &[[0.0, 0.0], [1.0, 0.0]],
+            &[[1.0, 0.0], [2.0, 1.0]],
+            &[[1.0, 0.0], [2.0, -1.0]],
+        ]);
+        let merged = junction.line_merge(&context).unwrap();
+        assert_eq!(merged.get_num_geometries().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_densify() {
+        let context = SimpleContextHandle::new();
+
+        let lines = multi_line_string(&context, &[&[[0.0, 0.0], [10.0, 0.0], [10.0, 1.0]]]);
+        let densified = lines.densify(&context, 3.0).unwrap();
+        assert_eq!(densified.geometry_type(), GeometryTypes::MultiLineString);
+        let cs = densified.get_geometry_n(0).unwrap().get_coord_sequence().unwrap();
+        // 10 long segment split in 4, the 1 long one is kept
+        assert_eq!(cs.num_points().unwrap(), 6);
+        assert_eq!(cs.get_x(1).unwrap(), 2.5);
+        assert_eq!(cs.get_x(4).unwrap(), 10.0);
+
+        let rectangle = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 4.0, 2.0).unwrap();
+        let densified = rectangle.densify(&context, 1.0).unwrap();
+        let ring = densified.get_exterior_ring().unwrap();
+        assert_eq!(ring.get_coord_sequence().unwrap().num_points().unwrap(), 13);
+        assert_eq!(densified.area().unwrap(), rectangle.area().unwrap());
+
+        assert!(rectangle.densify(&context, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_negative_buffer() {
+        let context = SimpleContextHandle::new();
+        let square = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap();
+        assert!(!square.is_empty().unwrap());
+
+        let shrunk = square.buffer(&context, -0.25, 8).unwrap();
+        assert!(!shrunk.is_empty().unwrap());
+        assert!((shrunk.area().unwrap() - 0.25).abs() < 1e-9);
+
+        let gone = square.buffer(&context, -1.0, 8).unwrap();
+        assert!(gone.is_empty().unwrap());
+        assert_eq!(gone.area().unwrap(), 0.0);
+        for seq in gone.coord_seqs().unwrap() {
+            assert_eq!(seq.num_points().unwrap(), 0);
+            assert!(seq.get_x(0).is_err());
+        }
+        assert!(gone.bbox().is_err());
+    }
+
+    #[test]
+    fn test_total_coordinate_count() {
+        let context = SimpleContextHandle::new();
+        let rectangle = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap();
+        assert_eq!(rectangle.total_coordinate_count().unwrap(), 5);
+
+        let lines = multi_line_string(&context, &[
+            &[[0.0, 0.0], [1.0, 0.0]],
+            &[[0.0, 1.0], [1.0, 1.0], [2.0, 1.0]],
+        ]);
+        assert_eq!(lines.total_coordinate_count().unwrap(), 5);
+
+        let empty = SimpleGeometry::create_empty_collection(&context, GeometryTypes::MultiPolygon).unwrap();
+        assert_eq!(empty.total_coordinate_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_hausdorff_distance() {
+        let context = SimpleContextHandle::new();
+        let square = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 2.0, 2.0).unwrap();
+        let shifted = SimpleGeometry::create_rectangle(&context, 0.5, 0.0, 2.5, 2.0).unwrap();
+
+        assert_eq!(square.hausdorff_distance(&square).unwrap(), 0.0);
+        assert!((square.hausdorff_distance(&shifted).unwrap() - 0.5).abs() < 1e-9);
+        assert!((square.hausdorff_distance_densify(&shifted, 0.25).unwrap() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shared_paths() {
+        let context = SimpleContextHandle::new();
+
+        let a = multi_line_string(&context, &[&[[0.0, 0.0], [10.0, 0.0]]]);
+        let b = multi_line_string(&context, &[&[[5.0, 0.0], [15.0, 0.0]], &[[3.0, 0.0], [1.0, 0.0]]]);
+        let shared = a.shared_paths(&context, &b).unwrap();
+        assert_eq!(shared.geometry_type(), GeometryTypes::GeometryCollection);
+
+        let forward = shared.get_geometry_n(0).unwrap();
+        assert_eq!(forward.get_num_geometries().unwrap(), 1);
+        assert_eq!(forward.get_geometry_n(0).unwrap().get_coord_sequence().unwrap().get_x(0).unwrap(), 5.0);
+
+        let backward = shared.get_geometry_n(1).unwrap();
+        assert_eq!(backward.get_num_geometries().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_is_closed() {
+        let context = SimpleContextHandle::new();
+
+        let open = multi_line_string(&context, &[&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]]);
+        assert!(!open.get_geometry_n(0).unwrap().is_closed().unwrap());
+
+        let closed = multi_line_string(&context, &[&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]);
+        assert!(closed.get_geometry_n(0).unwrap().is_closed().unwrap());
+        assert!(closed.is_closed().unwrap());
+
+        let rectangle = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap();
+        assert!(rectangle.get_exterior_ring().unwrap().is_closed().unwrap());
+        assert!(rectangle.is_closed().is_err());
+    }
+
+    #[test]
+    fn test_thread_default_context() {
+        let main_handle = SimpleContextHandle::with_thread_default(|context| {
+            let rectangle = SimpleGeometry::create_rectangle(context, 0.0, 0.0, 2.0, 1.0).unwrap();
+            assert_eq!(rectangle.area().unwrap(), 2.0);
+            context.c_handle as usize
+        });
+        assert_eq!(main_handle, SimpleContextHandle::with_thread_default(|c| c.c_handle as usize));
+
+        let other_handle = ::std::thread::spawn(|| {
+            SimpleContextHandle::with_thread_default(|c| c.c_handle as usize)
+        })
+            .join()
+            .unwrap();
+        assert_ne!(main_handle, other_handle);
+    }
+
+    #[test]
+    fn test_clone() {
+        let context = SimpleContextHandle::new();
+
+        let rectangle = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 2.0, 1.0).unwrap();
+        let copy = rectangle.clone_geom().unwrap();
+        drop(rectangle);
+        assert_eq!(copy.area().unwrap(), 2.0);
+
+        // a borrowed part becomes an owned geometry that outlives its parent
+        let lines = multi_line_string(&context, &[&[[0.0, 0.0], [1.0, 0.0]], &[[5.0, 5.0], [5.0, 7.0]]]);
+        let second = lines.get_geometry_n(1).unwrap().clone_geom().unwrap();
+        drop(lines);
+        let expected = multi_line_string(&context, &[&[[5.0, 5.0], [5.0, 7.0]]]);
+        assert!(second.equals_exact(&expected.get_geometry_n(0).unwrap(), 0.0).unwrap());
+    }
+
+    #[test]
+    fn test_point_x_y() {
+        let context = SimpleContextHandle::new();
+
+        let point = SimpleGeometry::create_point_xy(&context, 3.0, -2.0).unwrap();
+        assert_eq!(point.x().unwrap(), 3.0);
+        assert_eq!(point.y().unwrap(), -2.0);
+
+        let rectangle = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 2.0, 1.0).unwrap();
+        let centroid = rectangle.centroid().unwrap();
+        assert_eq!(centroid.get_xy().unwrap(), (1.0, 0.5));
+
+        assert!(rectangle.x().is_err());
+        assert!(rectangle.y().is_err());
+    }
+
+    #[test]
+    fn test_to_wkt_precision() {
+        let context = SimpleContextHandle::new();
+        let point = SimpleGeometry::create_point_xy(&context, 1.123456, 2.5).unwrap();
+        assert_eq!(point.to_wkt_precision(2).unwrap(), "POINT (1.12 2.5)");
+        let point = SimpleGeometry::create_point_xy(&context, 0.4, 2.7).unwrap();
+        assert_eq!(point.to_wkt_precision(0).unwrap(), "POINT (0 3)");
+
+        let square = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap();
+        assert_eq!(square.to_wkt_precision(6).unwrap(), "POLYGON ((0 0, 1 0, 1 1, 0 1, 0 0))");
+    }
+
+    #[test]
+    fn test_wkt_writer() {
+        let context = SimpleContextHandle::new();
+        let point = SimpleGeometry::create_point_xy(&context, 1.123456, 2.5).unwrap();
+
+        let mut writer = WKTWriter::new(&context).unwrap();
+        writer.set_rounding_precision(3);
+        writer.set_trim(false);
+        assert_eq!(writer.write(&point).unwrap(), "POINT (1.123 2.500)");
+        writer.set_trim(true);
+        assert_eq!(writer.write(&point).unwrap(), "POINT (1.123 2.5)");
+    }
+
+    #[test]
+    fn test_force_2d() {
+        let context = SimpleContextHandle::new();
+
+        let mut cs = SimpleCoordinateSequence::new_with_dimensions(2, ::CoordDimensions::ThreeD, &context).unwrap();
+        for (i, &[x, y, z]) in [[0.0, 0.0, 10.0], [3.0, 4.0, 20.0]].iter().enumerate() {
+            cs.set_x(i as u32, x).unwrap();
+            cs.set_y(i as u32, y).unwrap();
+            cs.set_z(i as u32, z).unwrap();
+        }
+        let line = SimpleGeometry::create_line_string(cs).unwrap();
+        assert_eq!(line.coordinate_dimension().unwrap(), 3);
+
+        let line_2d = line.force_2d(&context).unwrap();
+        assert_eq!(line_2d.coordinate_dimension().unwrap(), 2);
+        assert_eq!(line_2d.to_wkt_precision(1).unwrap(), "LINESTRING (0 0, 3 4)");
+        // the source keeps its Z
+        assert_eq!(line.coordinate_dimension().unwrap(), 3);
+
+        let point = SimpleGeometry::create_point_xy(&context, 1.0, 2.0).unwrap();
+        assert_eq!(point.force_2d(&context).unwrap().coordinate_dimension().unwrap(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "v3_8_0")]
+    fn test_build_area() {
+        let context = SimpleContextHandle::new();
+
+        // three nested squares: the middle one is a hole, the inner one an island in it
+        let linework = multi_line_string(&context, &[
+            &[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+            &[[2.0, 2.0], [8.0, 2.0], [8.0, 8.0], [2.0, 8.0], [2.0, 2.0]],
+            &[[4.0, 4.0], [6.0, 4.0], [6.0, 6.0], [4.0, 6.0], [4.0, 4.0]],
+        ]);
+
+        let area = linework.build_area(&context).unwrap();
+        assert_eq!(area.geometry_type(), GeometryTypes::MultiPolygon);
+        assert_eq!(area.get_num_geometries().unwrap(), 2);
+        assert!((area.area().unwrap() - 68.0).abs() < 1e-9);
+
+        let island = SimpleGeometry::create_point_xy(&context, 5.0, 5.0).unwrap();
+        let hole = SimpleGeometry::create_point_xy(&context, 3.0, 3.0).unwrap();
+        assert!(area.covers(&island).unwrap());
+        assert!(!area.covers(&hole).unwrap());
+    }
+
+    #[test]
+    fn test_coord_seq_dimensions() {
+        use ::CoordDimensions;
+
+        let context = SimpleContextHandle::new();
+
+        let cs = SimpleCoordinateSequence::from_slice_pts(&[[0.0, 0.0], [1.0, 1.0]], &context).unwrap();
+        assert_eq!(cs.dimensions().unwrap(), CoordDimensions::TwoD);
+        assert!(!cs.has_z().unwrap());
+
+        let mut cs = SimpleCoordinateSequence::new_with_dimensions(2, CoordDimensions::ThreeD, &context).unwrap();
+        for i in 0..2 {
+            cs.set_x(i, i as f64).unwrap();
+            cs.set_y(i, 2.0).unwrap();
+            cs.set_z(i, 3.0).unwrap();
+        }
+        assert_eq!(cs.dimensions().unwrap(), CoordDimensions::ThreeD);
+        assert!(cs.has_z().unwrap());
+        assert_eq!(cs.get_z(1).unwrap(), 3.0);
+
+        // the sequence of a geometry keeps its dimensions
+        let line = SimpleGeometry::create_line_string(cs).unwrap();
+        assert!(line.get_coord_sequence().unwrap().has_z().unwrap());
+        let square = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap();
+        assert!(!square.get_exterior_ring().unwrap().get_coord_sequence().unwrap().has_z().unwrap());
+    }
+
+    #[test]
+    fn test_flatten_collection() {
+        let context = SimpleContextHandle::new();
+        let point = |x: f64, y: f64| SimpleGeometry::create_point_xy(&context, x, y).unwrap();
+
+        let multi_point = SimpleGeometry::create_multi_geom(
+            &context, vec![point(5.0, 5.0), point(6.0, 6.0)], GeometryTypes::MultiPoint).unwrap();
+        let inner = SimpleGeometry::create_multi_geom(
+            &context,
+            vec![
+                SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 1.0, 1.0).unwrap(),
+                multi_point,
+                SimpleGeometry::create_empty_collection(&context, GeometryTypes::GeometryCollection).unwrap(),
+            ],
+            GeometryTypes::GeometryCollection,
+        ).unwrap();
+        let lines = multi_line_string(&context, &[&[[0.0, 0.0], [1.0, 0.0]], &[[5.0, 5.0], [5.0, 7.0]]]);
+        let nested = SimpleGeometry::create_multi_geom(
+            &context, vec![point(9.0, 9.0), lines, inner], GeometryTypes::GeometryCollection).unwrap();
+
+        let flat = nested.flatten_collection(&context).unwrap();
+        assert_eq!(flat.geometry_type(), GeometryTypes::GeometryCollection);
+        let types: Vec<GeometryTypes> = (0..flat.get_num_geometries().unwrap())
+            .map(|i| flat.get_geometry_n(i).unwrap().geometry_type())
+            .collect();
+        assert_eq!(types, vec![
+            GeometryTypes::Point,
+            GeometryTypes::LineString,
+            GeometryTypes::LineString,
+            GeometryTypes::Polygon,
+            GeometryTypes::Point,
+            GeometryTypes::Point,
+        ]);
+
+        // the source is left untouched
+        assert_eq!(nested.get_num_geometries().unwrap(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "v3_6_0")]
+    fn test_minimum_clearance_and_width() {
+        let context = SimpleContextHandle::new();
+
+        // 10 x 2: the long sides are 2 apart
+        let rectangle = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 10.0, 2.0).unwrap();
+        assert!((rectangle.minimum_clearance().unwrap() - 2.0).abs() < 1e-9);
+        assert!((rectangle.minimum_width().unwrap() - 2.0).abs() < 1e-9);
+
+        let thin = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 10.0, 0.01).unwrap();
+        assert!((thin.minimum_clearance().unwrap() - 0.01).abs() < 1e-9);
+
+        // nothing to move a single point towards
+        let point = SimpleGeometry::create_point_xy(&context, 1.0, 1.0).unwrap();
+        assert_eq!(point.minimum_clearance().unwrap(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_covered_by_and_within() {
+        let context = SimpleContextHandle::new();
+
+        let square = SimpleGeometry::create_rectangle(&context, 0.0, 0.0, 10.0, 10.0).unwrap();
+        let inside = SimpleGeometry::create_rectangle(&context, 2.0, 2.0, 4.0, 4.0).unwrap();
+        // only touches the boundary: covered by the square but not within it
+        let edge = multi_line_string(&context, &[&[[0.0, 0.0], [10.0, 0.0]]]);
+        let outside = SimpleGeometry::create_rectangle(&context, 20.0, 20.0, 30.0, 30.0).unwrap();
+
+        assert!(inside.covered_by(&square).unwrap());
+        assert!(inside.within(&square).unwrap());
+        assert!(edge.covered_by(&square).unwrap());
+        assert!(!edge.within(&square).unwrap());
+        assert!(!outside.covered_by(&square).unwrap());
+
+        let geoms = [&square, &inside, &edge, &outside];
+        for a in geoms.iter() {
+            for b in geoms.iter() {
+                assert_eq!(a.covered_by(b).unwrap(), b.covers(a).unwrap());
+                assert_eq!(a.within(b).unwrap(), b.contains(a).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "v3_7_0")]
+    fn test_frechet_distance() {
+        let context = SimpleContextHandle::new();
+        let line = |pts: &[[f64; 2]]| {
+            SimpleGeometry::create_line_string(SimpleCoordinateSequence::from_slice_pts(pts, &context).unwrap()).unwrap()
+        };
+
+        // the example of the GEOS documentation
+        let a = line(&[[0.0, 0.0], [100.0, 0.0]]);
+        let b = line(&[[0.0, 0.0], [50.0, 50.0], [100.0, 0.0]]);
+        assert!((a.frechet_distance(&b).unwrap() - 50.0 * 2f64.sqrt()).abs() < 1e-9);
+        // with the vertex at (50, 0) the apex of b is reached straight down
+        assert!((a.frechet_distance_densify(&b, 0.5).unwrap() - 50.0).abs() < 1e-9);
+
+        assert_eq!(a.frechet_distance(&a).unwrap(), 0.0);
+    }
+}
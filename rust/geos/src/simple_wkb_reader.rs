@@ -57,7 +57,7 @@ impl<'c> WKBReader<'c> {
     /// ```
 
     /// ```
-    pub fn read_wkb(&self, bytes: &[u8]) -> Result<SimpleGeometry> {
+    pub fn read_wkb(&self, bytes: &[u8]) -> Result<SimpleGeometry<'c>> {
 
         unsafe {
             let w_ptr = GEOSWKBReader_read_r(
@@ -0,0 +1,105 @@
+/*
+This file is part of the Building Aggregration Tool
+Copyright (C) 2022 Novel-T
+
+The Building Aggregration Tool is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+use enums::OutputDimension;
+use geos_sys::*;
+use simple_string::simple_managed_string;
+use ::{SimpleContextHandle, SimpleGeometry};
+use anyhow::{bail, Result};
+
+/// The `WKTWriter` type is used to generate `WKT` formatted output from [`SimpleGeometry`],
+/// with control over the number of decimals and trailing zeros.
+///
+/// Comparing WKT written with a fixed precision is stable across GEOS / PROJ versions,
+/// unlike the full precision output of `to_wkt`.
+pub struct WKTWriter<'c> {
+    pub(crate) c_handle: *mut GEOSWKTWriter,
+    pub(crate) context_handle: &'c SimpleContextHandle
+}
+
+impl<'c> WKTWriter<'c> {
+    /// Creates a new `WKTWriter` instance with the GEOS defaults (full precision, no trim).
+    pub fn new(context: &'c SimpleContextHandle) -> Result<WKTWriter<'c>> {
+        unsafe {
+            let ptr = GEOSWKTWriter_create_r(context.c_handle);
+
+            if ptr.is_null() {
+                bail!("GEOSWKTWriter_create_r");
+            }
+
+            Ok(WKTWriter {
+                c_handle: ptr,
+                context_handle: context
+            })
+        }
+    }
+
+    /// Creates a writer rounding to `precision` decimals, with trailing zeros trimmed.
+    pub fn new_with_precision(context: &'c SimpleContextHandle, precision: u32) -> Result<WKTWriter<'c>> {
+        let mut writer = WKTWriter::new(context)?;
+        writer.set_rounding_precision(precision);
+        writer.set_trim(true);
+        Ok(writer)
+    }
+
+    /// Writes out the given `geometry` as WKT format.
+    pub fn write(&self, geometry: &SimpleGeometry) -> Result<String> {
+        unsafe {
+            let ptr = GEOSWKTWriter_write_r(self.context_handle.c_handle, self.c_handle, geometry.c_handle);
+            simple_managed_string(ptr, self.context_handle)
+        }
+    }
+
+    /// Sets the number of decimals used for the coordinates.
+    pub fn set_rounding_precision(&mut self, precision: u32) {
+        unsafe {
+            GEOSWKTWriter_setRoundingPrecision_r(
+                self.context_handle.c_handle,
+                self.c_handle,
+                precision as _,
+            )
+        }
+    }
+
+    /// When trim is on, trailing zeros are removed, i.e. `1.500000` is written `1.5`.
+    pub fn set_trim(&mut self, trim: bool) {
+        unsafe {
+            GEOSWKTWriter_setTrim_r(
+                self.context_handle.c_handle,
+                self.c_handle,
+                trim as _,
+            )
+        }
+    }
+
+    /// Sets the number of dimensions to be written. By default, it is 2.
+    pub fn set_output_dimension(&mut self, dimension: OutputDimension) {
+        unsafe {
+            GEOSWKTWriter_setOutputDimension_r(
+                self.context_handle.c_handle,
+                self.c_handle,
+                dimension.into(),
+            )
+        }
+    }
+}
+
+impl<'a> Drop for WKTWriter<'a> {
+    fn drop(&mut self) {
+        unsafe { GEOSWKTWriter_destroy_r(self.context_handle.c_handle, self.c_handle) };
+    }
+}
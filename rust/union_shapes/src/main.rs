@@ -132,10 +132,10 @@ fn run() -> Result<()> {
         debug!("Extent for {}", idx);
         let extent = in_layer.get_extent(true).unwrap();
 
-        total_extent.MinX = float_min(total_extent.MinX, extent.MinX);
-        total_extent.MinY = float_min(total_extent.MinY, extent.MinY);
-        total_extent.MaxX = float_max(total_extent.MaxX, extent.MaxX);
-        total_extent.MaxY = float_max(total_extent.MaxY, extent.MaxY);
+        total_extent.MinX = float_min(total_extent.MinX, extent.min_x);
+        total_extent.MinY = float_min(total_extent.MinY, extent.min_y);
+        total_extent.MaxX = float_max(total_extent.MaxX, extent.max_x);
+        total_extent.MaxY = float_max(total_extent.MaxY, extent.max_y);
 
         total += in_layer.count(false);
     }